@@ -0,0 +1,271 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// The rank of a card, from two up to the ace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Rank {
+    Two = 2,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+    /// Jokers are only dealt when `Rules::jokers` asks for them, and are
+    /// always wild.
+    Joker,
+}
+
+impl Rank {
+    /// Every standard rank, lowest first, leaving out the joker.
+    pub const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    /// The face value of the rank: 2 to 10, then 11 to 14 for jack, queen,
+    /// king and ace, and 15 for the joker.
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+
+    /// The standard rank with the given face value, if there is one.
+    pub fn from_value(value: u8) -> Option<Rank> {
+        match value {
+            2..=14 => Some(Rank::ALL[value as usize - 2]),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Rank::Ten => f.write_str("T"),
+            Rank::Jack => f.write_str("J"),
+            Rank::Queen => f.write_str("Q"),
+            Rank::King => f.write_str("K"),
+            Rank::Ace => f.write_str("A"),
+            Rank::Joker => f.write_str("JK"),
+            rank => write!(f, "{}", rank.value()),
+        }
+    }
+}
+
+impl FromStr for Rank {
+    type Err = String;
+
+    /// Parse `2` to `9`, `T` or `10`, `J`, `Q`, `K`, `A` or `JK` for the
+    /// joker, in either case.
+    fn from_str(s: &str) -> Result<Rank, String> {
+        let rank = match s {
+            "JK" | "jk" => Some(Rank::Joker),
+            "T" | "t" => Some(Rank::Ten),
+            "J" | "j" => Some(Rank::Jack),
+            "Q" | "q" => Some(Rank::Queen),
+            "K" | "k" => Some(Rank::King),
+            "A" | "a" => Some(Rank::Ace),
+            _ => s.parse().ok().and_then(Rank::from_value),
+        };
+        rank.ok_or_else(|| format!("invalid rank {:?}", s))
+    }
+}
+
+/// The suit of a card. Suits never decide a trick in the standard game, but
+/// they are kept so that each physical card can be told apart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suit {
+    /// The suit given to cards written without one.
+    #[default]
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    /// Every suit, in the order dealt into a sorted deck.
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Suit::Clubs => "C",
+            Suit::Diamonds => "D",
+            Suit::Hearts => "H",
+            Suit::Spades => "S",
+        })
+    }
+}
+
+impl FromStr for Suit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Suit, String> {
+        match s {
+            "C" | "c" => Ok(Suit::Clubs),
+            "D" | "d" => Ok(Suit::Diamonds),
+            "H" | "h" => Ok(Suit::Hearts),
+            "S" | "s" => Ok(Suit::Spades),
+            _ => Err(format!("invalid suit {:?}", s)),
+        }
+    }
+}
+
+/// A playing card.
+///
+/// Cards are written as their rank, `2` to `9`, `T`, `J`, `Q`, `K`, `A` or
+/// `JK`, followed by a suit letter, `C`, `D`, `H` or `S`, as in `TD` or `AS`.
+///
+/// Cards are packed into a single byte, the rank's value in the low four
+/// bits and the suit in the two above, so decks stay as compact as they were
+/// when cards were bare ranks. Only valid cards can be built.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Card(u8);
+
+impl Card {
+    /// A card of `rank` in `suit`.
+    pub fn new(rank: Rank, suit: Suit) -> Card {
+        Card(rank.value() | (suit as u8) << 4)
+    }
+
+    /// The card's rank, `Rank::Joker` for jokers.
+    pub fn rank(self) -> Rank {
+        match self.0 & 0xF {
+            15 => Rank::Joker,
+            value => Rank::ALL[value as usize - 2],
+        }
+    }
+
+    /// The card's suit.
+    pub fn suit(self) -> Suit {
+        Suit::ALL[(self.0 >> 4) as usize]
+    }
+
+    #[inline]
+    pub fn is_joker(self) -> bool {
+        self.0 & 0xF == Rank::Joker.value()
+    }
+
+    /// Compare the ranks of two cards, ignoring their suits.
+    #[inline]
+    pub fn cmp_rank(self, other: Card) -> Ordering {
+        (self.0 & 0xF).cmp(&(other.0 & 0xF))
+    }
+
+    /// The packed representation, which is always below 64.
+    pub fn to_byte(self) -> u8 {
+        self.0
+    }
+
+    /// The card packed into `byte` by `to_byte`, if it is one.
+    pub fn from_byte(byte: u8) -> Option<Card> {
+        let rank = match byte & 0xF {
+            15 => Rank::Joker,
+            value => Rank::from_value(value)?,
+        };
+        let suit = *Suit::ALL.get((byte >> 4) as usize)?;
+        Some(Card::new(rank, suit))
+    }
+}
+
+impl From<Rank> for Card {
+    /// The card of the given rank in the default suit.
+    fn from(rank: Rank) -> Card {
+        Card::new(rank, Suit::default())
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.rank(), self.suit())
+    }
+}
+
+impl fmt::Debug for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl FromStr for Card {
+    type Err = String;
+
+    /// Parse a rank optionally followed by a suit, such as `AS`, `10h` or
+    /// `7`. Cards without a suit get the default one.
+    fn from_str(s: &str) -> Result<Card, String> {
+        let invalid = || format!("invalid card {:?}", s);
+        match s.char_indices().last() {
+            Some((i, suit)) if i > 0 && suit.is_alphabetic() && !"TJQKAtjqka".contains(suit) => {
+                let rank = s[..i].parse().map_err(|_| invalid())?;
+                let suit = s[i..].parse().map_err(|_| invalid())?;
+                Ok(Card::new(rank, suit))
+            }
+            _ => s.parse::<Rank>().map(Card::from).map_err(|_| invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packing() {
+        for &rank in Rank::ALL.iter().chain(&[Rank::Joker]) {
+            for &suit in &Suit::ALL {
+                let card = Card::new(rank, suit);
+                assert_eq!((card.rank(), card.suit()), (rank, suit));
+                assert!(card.to_byte() < 64);
+                assert_eq!(Card::from_byte(card.to_byte()), Some(card));
+                assert_eq!(card.to_string().parse(), Ok(card));
+            }
+        }
+        assert_eq!(Card::from_byte(0), None);
+        assert_eq!(Card::from_byte(1), None);
+        assert_eq!(Card::from_byte(15), Some(Card::from(Rank::Joker)));
+        assert_eq!(Card::from_byte(64 | 2), None);
+    }
+
+    #[test]
+    fn notation() {
+        assert_eq!("AS".parse(), Ok(Card::new(Rank::Ace, Suit::Spades)));
+        assert_eq!("10h".parse(), Ok(Card::new(Rank::Ten, Suit::Hearts)));
+        assert_eq!("7".parse(), Ok(Card::new(Rank::Seven, Suit::Clubs)));
+        assert_eq!("a".parse(), Ok(Card::from(Rank::Ace)));
+        assert_eq!("JKD".parse(), Ok(Card::new(Rank::Joker, Suit::Diamonds)));
+        assert_eq!("jk".parse(), Ok(Card::from(Rank::Joker)));
+        for bad in &["", "1", "15", "X", "AX", "S", "2SS"] {
+            assert!(bad.parse::<Card>().is_err(), "{:?} parsed", bad);
+        }
+    }
+
+    #[test]
+    fn ranks_decide_comparison() {
+        let two = Card::new(Rank::Two, Suit::Spades);
+        let ace = Card::new(Rank::Ace, Suit::Clubs);
+        assert_eq!(two.cmp_rank(ace), Ordering::Less);
+        assert_eq!(ace.cmp_rank(two), Ordering::Greater);
+        assert_eq!(two.cmp_rank(Card::from(Rank::Two)), Ordering::Equal);
+    }
+}
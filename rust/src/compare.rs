@@ -0,0 +1,169 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use {Card, Rank};
+
+/// How the cards turned over in a trick are ranked against each other.
+///
+/// The comparison only looks at ranks; `Rules::suits_break_ties` adds the
+/// suits on top. `Cyclic` is not transitive, so when more than two players
+/// turn over a card the highest is found going round the table, computer
+/// first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    /// The higher rank wins, aces high.
+    #[default]
+    Standard,
+    /// The lower rank wins, as in the variant known as Peace.
+    Peace,
+    /// The higher rank wins, but aces count below twos.
+    AceLow,
+    /// The higher rank wins, except that the lowest rank in play beats the
+    /// highest, so a two takes an ace.
+    Cyclic,
+}
+
+impl Comparison {
+    /// Compare the ranks of `a` and `b`, `Greater` meaning `a` wins, in a
+    /// game played with `ranks`, lowest first.
+    #[inline]
+    pub fn compare(&self, a: Card, b: Card, ranks: &[Rank]) -> Ordering {
+        match *self {
+            Comparison::Standard => a.cmp_rank(b),
+            Comparison::Peace => b.cmp_rank(a),
+            Comparison::AceLow => ace_low(a.rank()).cmp(&ace_low(b.rank())),
+            Comparison::Cyclic => {
+                let (a, b) = (a.rank(), b.rank());
+                match (ranks.first(), ranks.last()) {
+                    (Some(&low), Some(&high)) if low != high && a == low && b == high => Ordering::Greater,
+                    (Some(&low), Some(&high)) if low != high && a == high && b == low => Ordering::Less,
+                    _ => a.cmp(&b),
+                }
+            }
+        }
+    }
+}
+
+/// The value of `rank` with aces counting as 1.
+fn ace_low(rank: Rank) -> u8 {
+    if rank == Rank::Ace {
+        1
+    } else {
+        rank.value()
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = String;
+
+    /// Parse `standard`, `peace`, `ace-low` or `cyclic`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "standard" => Ok(Comparison::Standard),
+            "peace" => Ok(Comparison::Peace),
+            "ace-low" => Ok(Comparison::AceLow),
+            "cyclic" => Ok(Comparison::Cyclic),
+            _ => Err(format!(
+                "unknown comparison {:?}, expected standard, peace, ace-low or cyclic",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Comparison::Standard => "standard",
+            Comparison::Peace => "peace",
+            Comparison::AceLow => "ace-low",
+            Comparison::Cyclic => "cyclic",
+        })
+    }
+}
+
+/// What a wild card, a joker or a card of one of `Rules::wild_ranks`, does
+/// when it is turned over in a trick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Wild {
+    /// It beats every card that is not wild, and ties with other wild cards.
+    #[default]
+    Beats,
+    /// It ties with the best card turned over, so its player goes to war
+    /// with whoever played that card, or with every other player who turned
+    /// over a wild card if nobody else did.
+    StartsWar,
+}
+
+impl FromStr for Wild {
+    type Err = String;
+
+    /// Parse `beats` or `war`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "beats" => Ok(Wild::Beats),
+            "war" => Ok(Wild::StartsWar),
+            _ => Err(format!("unknown wild card rule {:?}, expected beats or war", s)),
+        }
+    }
+}
+
+impl fmt::Display for Wild {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Wild::Beats => "beats",
+            Wild::StartsWar => "war",
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn compare(comparison: Comparison, a: &str, b: &str) -> Ordering {
+        comparison.compare(a.parse().unwrap(), b.parse().unwrap(), &Rank::ALL)
+    }
+
+    #[test]
+    fn comparisons() {
+        for &(comparison, two_vs_ace, two_vs_three, king_vs_ace) in &[
+            (Comparison::Standard, Ordering::Less, Ordering::Less, Ordering::Less),
+            (Comparison::Peace, Ordering::Greater, Ordering::Greater, Ordering::Greater),
+            (Comparison::AceLow, Ordering::Greater, Ordering::Less, Ordering::Greater),
+            (Comparison::Cyclic, Ordering::Greater, Ordering::Less, Ordering::Less),
+        ] {
+            assert_eq!(compare(comparison, "2", "A"), two_vs_ace, "{}", comparison);
+            assert_eq!(compare(comparison, "A", "2"), two_vs_ace.reverse(), "{}", comparison);
+            assert_eq!(compare(comparison, "2", "3"), two_vs_three, "{}", comparison);
+            assert_eq!(compare(comparison, "K", "A"), king_vs_ace, "{}", comparison);
+            assert_eq!(compare(comparison, "7C", "7S"), Ordering::Equal, "{}", comparison);
+        }
+    }
+
+    #[test]
+    fn cyclic_uses_the_ranks_in_play() {
+        let ranks = [Rank::Five, Rank::Six, Rank::Seven];
+        let compare = |a: &str, b: &str| Comparison::Cyclic.compare(a.parse().unwrap(), b.parse().unwrap(), &ranks);
+        assert_eq!(compare("5", "7"), Ordering::Greater);
+        assert_eq!(compare("5", "6"), Ordering::Less);
+        assert_eq!(compare("6", "7"), Ordering::Less);
+    }
+
+    #[test]
+    fn notation() {
+        for &comparison in &[
+            Comparison::Standard,
+            Comparison::Peace,
+            Comparison::AceLow,
+            Comparison::Cyclic,
+        ] {
+            assert_eq!(comparison.to_string().parse(), Ok(comparison));
+        }
+        assert!("low".parse::<Comparison>().is_err());
+        for &wild in &[Wild::Beats, Wild::StartsWar] {
+            assert_eq!(wild.to_string().parse(), Ok(wild));
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::vec_deque;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use {Card, Rank, Rules, Suit, WarRng};
+
+/// The algorithm used to shuffle a deck.
+///
+/// A seed must always deal the same deck so that recorded results stay
+/// valid. Each version is therefore frozen once released, and any change in
+/// behaviour is added as a new version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Shuffle {
+    /// Fisher-Yates from the bottom of the deck up: for each position `i`
+    /// from the last down to 1, swap it with a position drawn by
+    /// `rng.gen_range(0, i + 1)`. This matches `Rng::shuffle` in rand 0.3.
+    #[default]
+    V1,
+}
+
+/// A pile of cards, drawn from the front and added to at the back.
+///
+/// Decks are written top card first in card notation, as described for
+/// `Card`, separated by spaces or commas. `X*n` stands for `n` copies of card
+/// `X` and `[...]*n` for `n` copies of a run of cards, which keeps the
+/// 256-suit decks short. Cards written without a suit get the default one,
+/// and a deck whose cards are all in the default suit is written without
+/// suits:
+///
+/// ```
+/// use war::{Deck, Rank, Rules};
+///
+/// let rules = Rules { copies_per_rank: 8, ranks: vec![Rank::Two, Rank::Ace], ..Rules::default() };
+/// let deck = Deck::new_half_deck(&rules);
+/// assert_eq!(deck.to_string(), "[2C AC 2D AD 2H AH 2S AS]*2");
+/// assert_eq!(deck.to_string().parse(), Ok(deck));
+///
+/// let deck: Deck = "[2 3 4 5 6 7 8 9 T J Q K A]*256".parse().unwrap();
+/// assert_eq!(deck.len(), 13 * 256);
+/// assert_eq!(deck.to_string(), "[2 3 4 5 6 7 8 9 T J Q K A]*256");
+/// assert_eq!("AS KH 10d 2*3".parse::<Deck>().unwrap().to_string(), "AS KH TD 2C*3");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deck(VecDeque<Card>);
+impl Deck {
+    /// A sorted deck holding `rules.copies_per_rank` runs of `rules.ranks`
+    /// followed by `rules.jokers` jokers. The runs, then the jokers, take the
+    /// suits in turn, starting with clubs.
+    pub fn new_half_deck(rules: &Rules) -> Self {
+        let mut deck = VecDeque::with_capacity(rules.cards_per_player());
+        for copy in 0..rules.copies_per_rank {
+            let suit = Suit::ALL[copy % Suit::ALL.len()];
+            for &rank in &rules.ranks {
+                deck.push_back(Card::new(rank, suit));
+            }
+        }
+        for joker in 0..rules.jokers {
+            deck.push_back(Card::new(Rank::Joker, Suit::ALL[joker % Suit::ALL.len()]));
+        }
+        Deck(deck)
+    }
+
+    /// A half deck shuffled with the default `Shuffle` version.
+    pub fn new_shuffle<R: WarRng + ?Sized>(rules: &Rules, rng: &mut R) -> Self {
+        let mut deck = Self::new_half_deck(rules);
+        deck.shuffle(Shuffle::default(), rng);
+        deck
+    }
+
+    /// Randomly reorder every card in the deck.
+    pub fn shuffle<R: WarRng + ?Sized>(&mut self, version: Shuffle, rng: &mut R) {
+        let cards = self.0.make_contiguous();
+        match version {
+            Shuffle::V1 => {
+                let mut i = cards.len();
+                while i >= 2 {
+                    i -= 1;
+                    cards.swap(i, rng.gen_range(0, i + 1));
+                }
+            }
+        }
+    }
+
+    /// A deck holding no cards.
+    pub fn new_empty() -> Self {
+        Deck(VecDeque::new())
+    }
+
+    /// A deck holding the given cards, with the first element on top.
+    pub fn from_vec(vec: Vec<Card>) -> Self {
+        Deck(From::from(vec))
+    }
+
+    /// Take the top card, if any.
+    pub fn draw(&mut self) -> Option<Card> {
+        self.0.pop_front()
+    }
+
+    /// Take the bottom card, if any.
+    pub fn take_last(&mut self) -> Option<Card> {
+        self.0.pop_back()
+    }
+
+    /// Put all cards of `pile` on top of the deck, keeping their order.
+    pub fn put_on_top(&mut self, pile: Deck) {
+        for x in pile.0.into_iter().rev() {
+            self.0.push_front(x);
+        }
+    }
+
+    /// Put a card on the bottom of the deck.
+    pub fn add(&mut self, card: Card) {
+        self.0.push_back(card);
+    }
+
+    /// Put all cards of `pile` on the bottom of the deck, keeping their order.
+    pub fn add_pile(&mut self, pile: Deck) {
+        for x in pile.0 {
+            self.add(x);
+        }
+    }
+
+    /// Put copies of all cards of `pile` on the bottom of the deck, keeping
+    /// their order.
+    pub fn add_all(&mut self, pile: &Deck) {
+        self.0.extend(pile.0.iter().cloned());
+    }
+
+    /// Remove every card, keeping the allocation for reuse.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Number of cards in the deck.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the deck holds no cards.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The cards from top to bottom.
+    pub fn to_vec(&self) -> Vec<Card> {
+        self.0.iter().cloned().collect()
+    }
+
+    /// Iterate over the cards from top to bottom.
+    pub fn iter(&self) -> vec_deque::Iter<'_, Card> {
+        self.0.iter()
+    }
+}
+
+/// Write `cards` with runs of a repeated card shortened to `X*n`.
+/// Suits are left out when `suits` is false.
+fn write_runs(f: &mut fmt::Formatter, cards: &[Card], suits: bool) -> fmt::Result {
+    let mut first = true;
+    for run in cards.chunk_by(|a, b| a == b) {
+        if !first {
+            f.write_str(" ")?;
+        }
+        first = false;
+        if suits {
+            write!(f, "{}", run[0])?;
+        } else {
+            write!(f, "{}", run[0].rank())?;
+        }
+        if run.len() > 1 {
+            write!(f, "*{}", run.len())?;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Deck {
+    /// Write the deck in card notation, as `[...]*n` if it consists of
+    /// repeated runs.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cards = self.to_vec();
+        let suits = cards.iter().any(|card| card.suit() != Suit::default());
+        let len = cards.len();
+        let period = (1..len)
+            .filter(|&p| len.is_multiple_of(p))
+            .find(|&p| cards[p..].iter().zip(&cards[..]).all(|(a, b)| a == b));
+        match period {
+            Some(p) if cards[..p].windows(2).any(|pair| pair[0] != pair[1]) => {
+                f.write_str("[")?;
+                write_runs(f, &cards[..p], suits)?;
+                write!(f, "]*{}", len / p)
+            }
+            _ => write_runs(f, &cards, suits),
+        }
+    }
+}
+
+impl FromStr for Deck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Deck, String> {
+        let mut tokens = Vec::new();
+        let mut word = String::new();
+        for c in s.chars().chain(Some(' ')) {
+            if c.is_alphanumeric() {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                tokens.push(word.clone());
+                word.clear();
+            }
+            match c {
+                '[' | ']' | '*' => tokens.push(c.to_string()),
+                ',' => (),
+                _ if c.is_whitespace() => (),
+                _ => return Err(format!("unexpected {:?} in deck", c)),
+            }
+        }
+
+        let mut tokens = tokens.iter().map(|token| &token[..]).peekable();
+        let mut stack: Vec<Vec<Card>> = vec![Vec::new()];
+        while let Some(token) = tokens.next() {
+            let cards = match token {
+                "[" => {
+                    stack.push(Vec::new());
+                    continue;
+                }
+                "]" if stack.len() > 1 => stack.pop().unwrap(),
+                "]" => return Err("unmatched ]".to_string()),
+                "*" => return Err("* must follow a card or ]".to_string()),
+                card => vec![card.parse()?],
+            };
+            let count = if tokens.peek() == Some(&"*") {
+                tokens.next();
+                match tokens.next().map(str::parse::<usize>) {
+                    Some(Ok(count)) => count,
+                    _ => return Err("* must be followed by a count".to_string()),
+                }
+            } else {
+                1
+            };
+            let run = stack.last_mut().unwrap();
+            for _ in 0..count {
+                run.extend_from_slice(&cards);
+            }
+        }
+        if stack.len() > 1 {
+            return Err("missing ]".to_string());
+        }
+        Ok(Deck::from_vec(stack.pop().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{Rng, SeedableRng, StdRng};
+    use {Isaac64, Rank};
+
+    fn rand_rng(seed: usize) -> StdRng {
+        SeedableRng::from_seed(&[seed][..])
+    }
+
+    fn rng(seed: usize) -> Isaac64 {
+        Isaac64::from_seed(&[seed])
+    }
+
+    #[test]
+    fn shuffle_v1_is_pinned() {
+        let rules = Rules {
+            copies_per_rank: 1,
+            ..Rules::default()
+        };
+        let deck = Deck::new_shuffle(&rules, &mut rng(1));
+        assert_eq!(deck.to_string(), "3 5 8 7 T 6 2 9 Q A K 4 J");
+    }
+
+    #[test]
+    fn shuffle_matches_rand() {
+        let rules = Rules::default();
+        let mut expected = Deck::new_half_deck(&rules).to_vec();
+        rand_rng(7).shuffle(&mut expected);
+        assert_eq!(Deck::new_shuffle(&rules, &mut rng(7)).to_vec(), expected);
+    }
+
+    #[test]
+    fn shuffle_covers_wrapped_deck() {
+        let all: Vec<Card> = Suit::ALL
+            .iter()
+            .flat_map(|&suit| Rank::ALL.iter().map(move |&rank| Card::new(rank, suit)))
+            .collect();
+
+        // Pushing to the front makes the deque wrap around its buffer, so
+        // its cards are split over two slices.
+        let mut cards = VecDeque::with_capacity(64);
+        for &card in &all[..26] {
+            cards.push_back(card);
+        }
+        for &card in &all[26..] {
+            cards.push_front(card);
+        }
+        let mut deck = Deck(cards);
+        assert!(!deck.0.as_slices().1.is_empty());
+
+        let mut expected = deck.to_vec();
+        rand_rng(3).shuffle(&mut expected);
+        deck.shuffle(Shuffle::V1, &mut rng(3));
+        assert_eq!(deck.to_vec(), expected);
+
+        let mut sorted = deck.to_vec();
+        sorted.sort_by_key(|card| card.to_byte());
+        assert_eq!(sorted, all);
+    }
+
+    #[test]
+    fn half_deck_suits() {
+        let rules = Rules {
+            copies_per_rank: 5,
+            ranks: vec![Rank::Two, Rank::Ace],
+            ..Rules::default()
+        };
+        assert_eq!(Deck::new_half_deck(&rules).to_string(), "2C AC 2D AD 2H AH 2S AS 2C AC");
+    }
+
+    #[test]
+    fn half_deck_jokers() {
+        let rules = Rules {
+            copies_per_rank: 2,
+            ranks: vec![Rank::Two, Rank::Ace],
+            jokers: 3,
+            ..Rules::default()
+        };
+        let deck = Deck::new_half_deck(&rules);
+        assert_eq!(deck.len(), rules.cards_per_player());
+        assert_eq!(deck.to_string(), "2C AC 2D AD JKC JKD JKH");
+    }
+
+    #[test]
+    fn notation() {
+        let deck = |s: &str| s.parse::<Deck>().map(|deck| deck.to_string());
+        assert_eq!(deck("2 3 T J Q K A"), Ok("2 3 T J Q K A".to_string()));
+        assert_eq!(deck("as,kh, 10C 2d"), Ok("AS KH TC 2D".to_string()));
+        assert_eq!(deck("2 A*3 [3 [4]*2]*2"), Ok("2 A*3 3 4*2 3 4*2".to_string()));
+        assert_eq!(deck(""), Ok(String::new()));
+        for bad in &["X", "1", "15", "*3 2", "A*", "[2 3", "2]", "2;3", "[2]*x"] {
+            assert!(deck(bad).is_err(), "{:?} parsed", bad);
+        }
+    }
+
+    #[test]
+    fn notation_round_trips() {
+        let rules = Rules {
+            copies_per_rank: 4,
+            ..Rules::default()
+        };
+        let deck = |s: &str| s.parse::<Deck>().unwrap();
+        for deck in &[
+            Deck::new_half_deck(&rules),
+            Deck::new_shuffle(&rules, &mut rng(2)),
+            deck("2 A A A 2"),
+            deck("9 9 9 9"),
+            deck("2S 2S AH"),
+            Deck::new_empty(),
+        ] {
+            assert_eq!(deck.to_string().parse::<Deck>().as_ref(), Ok(deck));
+        }
+        assert_eq!(deck("2 A A A 2").to_string(), "2 A*3 2");
+        assert_eq!(deck("9 9 9 9").to_string(), "9*4");
+        assert_eq!(deck("2 2 3 2 2 3").to_string(), "[2*2 3]*2");
+        assert_eq!(deck("2 2 3 2 2 3S").to_string(), "2C*2 3C 2C*2 3S");
+    }
+}
@@ -0,0 +1,1019 @@
+use std::cmp::Ordering;
+use std::mem;
+
+use {
+    Card, Deck, Event, Exhaustion, NoObserver, NoStrategy, Observer, Rules, Score, Shuffle, Strategy, View,
+    WarRng, Xoshiro256StarStar,
+};
+
+/// The seat of the computer, which is dealt a sorted deck.
+pub const COMPUTER: usize = 0;
+
+/// The seat of the player whose result `Score` reports.
+pub const PLAYER: usize = 1;
+
+/// The outcome of playing a single trick.
+#[derive(Debug, PartialEq)]
+pub enum GameStepped {
+    /// The game goes on from the contained state.
+    Cont(GameState),
+    /// The game is over.
+    Done(Score),
+}
+
+/// The decks held by every seat at the table, plus counts of the tricks and
+/// wars played so far.
+///
+/// Seat `COMPUTER` holds the computer's deck and seat `PLAYER` the
+/// player's; any further seats are extra players. Each trick, every player
+/// still in the game plays a card and the highest card takes them all. When
+/// several players tie for the highest card, only they go to war. A player
+/// who has to play a card but has none left is knocked out, and the game
+/// ends once at most one player is left in it. With two seats this is the
+/// classic game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameState {
+    decks: Vec<Deck>,
+    /// Seats knocked out so far, in order, with the trick they went out in.
+    out: Vec<(usize, usize)>,
+    moves: usize,
+    wars: usize,
+}
+impl GameState {
+    /// A fresh game for `rules.players` seats, dealt in seat order: the
+    /// computer's half deck from `rules.computer_deck`, a sorted one by
+    /// default, and every other seat's from `rules.player_deck`, shuffled
+    /// by `rng` by default.
+    pub fn new<R: WarRng + ?Sized>(rules: &Rules, rng: &mut R) -> Self {
+        let mut decks = vec![rules.computer_deck.deal(rules, rng)];
+        for _ in 1..rules.players {
+            decks.push(rules.player_deck.deal(rules, rng));
+        }
+        GameState::with_players(decks, 0)
+    }
+
+    /// A two-player game starting from the given decks after `moves` tricks.
+    pub fn from_decks(computer: Deck, player: Deck, moves: usize) -> Self {
+        GameState::with_players(vec![computer, player], moves)
+    }
+
+    /// A two-player game restored from the values returned by the
+    /// accessors.
+    pub fn from_parts(computer: Deck, player: Deck, moves: usize, wars: usize) -> Self {
+        GameState::from_seats(vec![computer, player], Vec::new(), moves, wars)
+    }
+
+    /// A game between one seat per deck, starting after `moves` tricks. There
+    /// must be at least two decks.
+    pub fn with_players(decks: Vec<Deck>, moves: usize) -> Self {
+        GameState::from_seats(decks, Vec::new(), moves, 0)
+    }
+
+    /// A game of any size restored from the values returned by the
+    /// accessors. There must be at least two decks, and the knocked out
+    /// seats must be distinct seats at the table.
+    pub fn from_seats(decks: Vec<Deck>, out: Vec<(usize, usize)>, moves: usize, wars: usize) -> Self {
+        assert!(decks.len() >= 2, "a game needs at least two players");
+        for (i, &(seat, _)) in out.iter().enumerate() {
+            assert!(
+                seat < decks.len() && out[..i].iter().all(|&(other, _)| other != seat),
+                "invalid knocked out seat {}",
+                seat
+            );
+        }
+        GameState {
+            decks,
+            out,
+            moves,
+            wars,
+        }
+    }
+
+    /// The computer's deck.
+    pub fn computer(&self) -> &Deck {
+        &self.decks[COMPUTER]
+    }
+
+    /// The player's deck.
+    pub fn player(&self) -> &Deck {
+        &self.decks[PLAYER]
+    }
+
+    /// The deck of every seat.
+    pub fn decks(&self) -> &[Deck] {
+        &self.decks
+    }
+
+    /// Number of seats at the table, including any knocked out.
+    pub fn players(&self) -> usize {
+        self.decks.len()
+    }
+
+    /// Whether `seat` is still in the game.
+    pub fn is_in(&self, seat: usize) -> bool {
+        self.out.iter().all(|&(out, _)| out != seat)
+    }
+
+    /// The seats knocked out so far, in order, each with the number of tricks
+    /// played before the one it went out in.
+    pub fn knocked_out(&self) -> &[(usize, usize)] {
+        &self.out
+    }
+
+    /// Number of tricks played so far.
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    /// Number of wars fought so far. A war that escalates into another war
+    /// counts twice.
+    pub fn wars(&self) -> usize {
+        self.wars
+    }
+
+    /// Whether every seat holds exactly the same cards in the same order,
+    /// regardless of how many moves it took to get here.
+    pub fn same_position(&self, other: &GameState) -> bool {
+        self.decks == other.decks
+    }
+
+    /// The finishing order so far, best first. Seats still in the game come
+    /// first, ranked by the cards they hold; then knocked out seats, the last
+    /// to go out first. Seats with as many cards, or knocked out in the same
+    /// trick, share a place.
+    pub fn standings(&self) -> Vec<Vec<usize>> {
+        let mut places: Vec<Vec<usize>> = Vec::new();
+        let mut playing: Vec<usize> = (0..self.decks.len()).filter(|&seat| self.is_in(seat)).collect();
+        playing.sort_by_key(|&seat| ::std::cmp::Reverse(self.decks[seat].len()));
+        for seat in playing {
+            match places.last_mut() {
+                Some(place) if self.decks[place[0]].len() == self.decks[seat].len() => place.push(seat),
+                _ => places.push(vec![seat]),
+            }
+        }
+        let mut last_trick = None;
+        for &(seat, moves) in self.out.iter().rev() {
+            match places.last_mut() {
+                Some(place) if last_trick == Some(moves) => place.push(seat),
+                _ => places.push(vec![seat]),
+            }
+            last_trick = Some(moves);
+        }
+        for place in &mut places {
+            place.sort();
+        }
+        places
+    }
+
+    /// Play one trick, including any wars it sets off.
+    pub fn step(self, rules: &Rules) -> GameStepped {
+        self.step_with(rules, &mut NoObserver)
+    }
+
+    /// Play one trick, reporting what happens to `observer`.
+    pub fn step_with<O: Observer>(self, rules: &Rules, observer: &mut O) -> GameStepped {
+        self.step_with_strategy(rules, &mut NoStrategy, observer)
+    }
+
+    /// Play one trick, letting `strategy` arrange the cards won and
+    /// reporting what happens to `observer`.
+    pub fn step_with_strategy<S: Strategy + ?Sized, O: Observer>(
+        mut self,
+        rules: &Rules,
+        strategy: &mut S,
+        observer: &mut O,
+    ) -> GameStepped {
+        match self.play_trick(rules, &mut Table::default(), strategy, observer) {
+            None => GameStepped::Cont(self),
+            Some(score) => {
+                observer.observe(Event::GameOver { score: &score });
+                GameStepped::Done(score)
+            }
+        }
+    }
+
+    fn outcome(&self, score: Score, face_down: usize) -> Outcome {
+        Outcome {
+            score,
+            moves: self.moves,
+            wars: self.wars,
+            face_down,
+            computer_cards: self.computer().len(),
+            player_cards: self.player().len(),
+            standings: self.standings(),
+        }
+    }
+
+    /// The trick the player was knocked out in, if they have been.
+    fn player_out(&self) -> Option<usize> {
+        self.out.iter().find(|&&(seat, _)| seat == PLAYER).map(|&(_, moves)| moves)
+    }
+
+    /// The player's score once at most one seat is left in the game.
+    fn final_score(&self) -> Score {
+        match self.player_out() {
+            None => Score::WinAfter(self.moves),
+            Some(moves) if moves == self.moves && self.out.len() == self.decks.len() => {
+                Score::TiedAt(moves)
+            }
+            Some(moves) => Score::LoseAfter(moves),
+        }
+    }
+
+    /// Finish a trick by adding the piles to the bottom of `winner`'s deck,
+    /// in the order given by the winner's pickup policy and then their
+    /// strategy.
+    fn collect<S: Strategy + ?Sized, O: Observer>(
+        &mut self,
+        rules: &Rules,
+        winner: usize,
+        piles: &[Deck],
+        strategy: &mut S,
+        observer: &mut O,
+    ) {
+        let pickup = rules.pickup(winner);
+        if strategy.arranges(winner) {
+            let mut won = Deck::new_empty();
+            pickup.pick_up(rules, winner, self.moves, piles, &mut won);
+            let mut cards = won.to_vec();
+            strategy.arrange(&mut cards, &View::new(winner, self.moves, piles, &self.decks, rules));
+            self.decks[winner].add_pile(Deck::from_vec(cards));
+        } else {
+            pickup.pick_up(rules, winner, self.moves, piles, &mut self.decks[winner]);
+        }
+        observer.observe(Event::TrickWon {
+            winner,
+            piles,
+            decks: &self.decks,
+        });
+        self.moves += 1;
+    }
+
+    /// Turn over the next card of each contender, leaving `None` for the
+    /// seats not playing a card. With `keep_last`, contenders down to their
+    /// last card keep it back.
+    fn draw(&mut self, table: &mut Table, keep_last: bool) {
+        for card in table.cards.iter_mut() {
+            *card = None;
+        }
+        for &seat in &table.contenders {
+            let deck = &mut self.decks[seat];
+            if !keep_last || deck.len() > 1 {
+                table.cards[seat] = deck.draw();
+            }
+        }
+    }
+
+    /// Under `Exhaustion::LastCard`, turn the last card laid by each
+    /// contender who has none left to draw back over, as long as someone
+    /// else drew a card to play against.
+    fn turn_last_card(table: &mut Table) {
+        let cards = &mut table.cards;
+        if table.contenders.iter().any(|&seat| cards[seat].is_some()) {
+            for &seat in &table.contenders {
+                if cards[seat].is_none() {
+                    cards[seat] = table.piles[seat].take_last();
+                }
+            }
+        }
+    }
+
+    /// Shuffle the cards laid by the seats still in the game and hand them
+    /// back, each seat getting as many as it laid, for
+    /// `Exhaustion::Reshuffle`.
+    fn reshuffle(&mut self, seed: usize, table: &mut Table) {
+        let seats: Vec<usize> = (0..self.decks.len()).filter(|&seat| self.is_in(seat)).collect();
+        let mut cards = Deck::new_empty();
+        for &seat in &seats {
+            cards.add_all(&table.piles[seat]);
+        }
+        let mut rng = Xoshiro256StarStar::from_seed(&[seed, self.moves]);
+        cards.shuffle(Shuffle::V1, &mut rng);
+        for &seat in &seats {
+            let mut back = Deck::new_empty();
+            for _ in 0..table.piles[seat].len() {
+                back.add(cards.draw().expect("every laid card is handed back"));
+            }
+            self.decks[seat].put_on_top(back);
+            table.piles[seat].clear();
+        }
+    }
+
+    /// Play one trick in place, returning the score if the game is over.
+    /// `table` only holds buffers reused from trick to trick.
+    fn play_trick<S: Strategy + ?Sized, O: Observer>(
+        &mut self,
+        rules: &Rules,
+        table: &mut Table,
+        strategy: &mut S,
+        observer: &mut O,
+    ) -> Option<Score> {
+        if self.moves >= rules.move_limit {
+            // A player knocked out has lost however the others fare.
+            let score = self
+                .player_out()
+                .map_or(Score::FinishWith(self.player().len()), Score::LoseAfter);
+            return Some(score);
+        }
+        observer.observe(Event::TrickStarted { moves: self.moves });
+
+        let seats = self.decks.len();
+        let by_rank = rules.compares_by_rank();
+        let compare = |a: Card, b: Card| if by_rank { a.cmp_rank(b) } else { rules.compare(a, b) };
+        table.reset(self);
+        let mut depth = 0;
+        let mut reshuffled = false;
+
+        loop {
+            self.draw(table, false);
+            if depth > 0 && rules.exhaustion == Exhaustion::LastCard {
+                GameState::turn_last_card(table);
+            }
+            if table.contenders.iter().any(|&seat| table.cards[seat].is_none()) {
+                for &seat in &table.contenders {
+                    if table.cards[seat].is_none() {
+                        self.out.push((seat, self.moves));
+                        observer.observe(Event::KnockedOut { seat });
+                    }
+                }
+                if self.out.len() + 1 >= seats {
+                    return Some(self.final_score());
+                }
+                let cards = &table.cards;
+                table.contenders.retain(|&seat| cards[seat].is_some());
+                if table.contenders.is_empty() {
+                    // Everyone at war ran out together, so the players left
+                    // play on for the cards on the table.
+                    let game = &*self;
+                    table.contenders.extend((0..seats).filter(|&seat| game.is_in(seat)));
+                    continue;
+                }
+            }
+
+            observer.observe(Event::Drawn { cards: &table.cards });
+            let mut high: Option<Card> = None;
+            let mut tied = 0;
+            let mut wild = None;
+            let mut wilds = 0;
+            for &seat in &table.contenders {
+                let card = table.cards[seat].expect("every contender drew a card");
+                table.piles[seat].add(card);
+                if rules.starts_war(card) {
+                    // Tied with the best card, whichever that turns out to be.
+                    wild = Some(card);
+                    wilds += 1;
+                    continue;
+                }
+                match high.map(|high| compare(card, high)) {
+                    None | Some(Ordering::Greater) => {
+                        high = Some(card);
+                        tied = 1;
+                    }
+                    Some(Ordering::Equal) => tied += 1,
+                    Some(Ordering::Less) => (),
+                }
+            }
+            tied += wilds;
+            let high = high.or(wild).expect("a trick has contenders");
+            let cards = &table.cards;
+            table
+                .contenders
+                .retain(|&seat| cards[seat].map(|card| compare(card, high)) == Some(Ordering::Equal));
+            if tied == 1 {
+                let winner = table.contenders[0];
+                self.collect(rules, winner, &table.piles, strategy, observer);
+                return None;
+            }
+
+            // Only the players who tied go to war.
+            self.wars += 1;
+            depth += 1;
+            let face_down = rules.face_down(high.rank());
+            observer.observe(Event::War { depth, face_down });
+            let need = face_down + 1;
+            let decks = &self.decks;
+            let short = table.contenders.iter().filter(|&&seat| decks[seat].len() < need).count();
+            if short > 0 && short < table.contenders.len() {
+                match rules.exhaustion {
+                    Exhaustion::Skip | Exhaustion::LastCard => (),
+                    Exhaustion::LoseImmediately => {
+                        for &seat in &table.contenders {
+                            if self.decks[seat].len() < need {
+                                let deck = mem::replace(&mut self.decks[seat], Deck::new_empty());
+                                table.piles[seat].add_pile(deck);
+                                self.out.push((seat, self.moves));
+                                observer.observe(Event::KnockedOut { seat });
+                            }
+                        }
+                        if self.out.len() + 1 >= seats {
+                            return Some(self.final_score());
+                        }
+                        let game = &*self;
+                        table.contenders.retain(|&seat| game.is_in(seat));
+                    }
+                    Exhaustion::PlayAlone => {
+                        let decks = &self.decks;
+                        table.contenders.retain(|&seat| decks[seat].len() >= need);
+                    }
+                    Exhaustion::Reshuffle(seed) => {
+                        if !reshuffled {
+                            reshuffled = true;
+                            self.reshuffle(seed, table);
+                            observer.observe(Event::Reshuffled);
+                            let game = &*self;
+                            table.contenders.clear();
+                            table.contenders.extend((0..seats).filter(|&seat| game.is_in(seat)));
+                            depth = 0;
+                            continue;
+                        }
+                    }
+                }
+                if table.contenders.len() == 1 {
+                    let winner = table.contenders[0];
+                    self.collect(rules, winner, &table.piles, strategy, observer);
+                    return None;
+                }
+            }
+            let keep_last = rules.exhaustion == Exhaustion::LastCard;
+            for _ in 0..face_down {
+                self.draw(table, keep_last);
+                observer.observe(Event::FaceDown { cards: &table.cards });
+                for &seat in &table.contenders {
+                    if let Some(card) = table.cards[seat] {
+                        table.piles[seat].add(card);
+                        table.face_down += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The cards on the table during a trick. Kept between tricks so that
+/// playing a trick does not allocate.
+#[derive(Debug, Default)]
+struct Table {
+    /// The cards played by each seat.
+    piles: Vec<Deck>,
+    /// The cards just turned over by each seat.
+    cards: Vec<Option<Card>>,
+    /// The seats still contending for the trick.
+    contenders: Vec<usize>,
+    /// Cards laid face down in wars since the table was set, across tricks.
+    face_down: usize,
+}
+
+impl Table {
+    /// Clear the table for a new trick in `game`.
+    fn reset(&mut self, game: &GameState) {
+        let seats = game.decks.len();
+        self.piles.resize(seats, Deck::new_empty());
+        for pile in &mut self.piles {
+            pile.clear();
+        }
+        self.cards.clear();
+        self.cards.resize(seats, None);
+        self.contenders.clear();
+        self.contenders.extend((0..seats).filter(|&seat| game.is_in(seat)));
+    }
+}
+
+/// A finished game with the statistics needed for reporting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub score: Score,
+    /// Tricks played.
+    pub moves: usize,
+    /// Wars fought, as counted by `GameState::wars`.
+    pub wars: usize,
+    /// Cards laid face down in those wars, not counting any laid before the
+    /// position the game was played from.
+    pub face_down: usize,
+    /// Cards held by the computer when the game ended, not counting any
+    /// left on the table by the final trick.
+    pub computer_cards: usize,
+    /// Cards held by the player when the game ended, counted the same way.
+    pub player_cards: usize,
+    /// The finishing order of every seat, as given by
+    /// `GameState::standings` when the game ended.
+    pub standings: Vec<Vec<usize>>,
+}
+
+/// Step the game until it finishes.
+pub fn play_game(game_state: GameState, rules: &Rules) -> Score {
+    play_game_outcome(game_state, rules).score
+}
+
+/// Step the game until it finishes, keeping statistics about the game.
+///
+/// Since play is deterministic, a game that ever returns to an earlier
+/// position loops forever. Such games are detected with Brent's algorithm
+/// and reported as `Score::Cycle` as soon as the loop has been walked once.
+/// Games where a seat picks up at random, wars are reshuffled, or a seat
+/// arranges its cards with a strategy that is not deterministic, never
+/// loop, and play on until they finish or reach the move limit.
+pub fn play_game_outcome(game_state: GameState, rules: &Rules) -> Outcome {
+    play_game_with(game_state, rules, &mut NoObserver)
+}
+
+/// Like `play_game_outcome`, reporting every event to `observer`.
+pub fn play_game_with<O: Observer>(game_state: GameState, rules: &Rules, observer: &mut O) -> Outcome {
+    play_game_with_strategy(game_state, rules, &mut NoStrategy, observer)
+}
+
+/// Like `play_game_with`, letting `strategy` arrange the cards each seat
+/// wins.
+pub fn play_game_with_strategy<S: Strategy + ?Sized, O: Observer>(
+    mut game_state: GameState,
+    rules: &Rules,
+    strategy: &mut S,
+    observer: &mut O,
+) -> Outcome {
+    let start = game_state.clone();
+    let mut saved = game_state.clone();
+    let mut table = Table::default();
+    let mut power = 1;
+    let mut period = 0;
+    let loops = rules.is_deterministic() && strategy.is_deterministic();
+    loop {
+        if let Some(score) = game_state.play_trick(rules, &mut table, strategy, observer) {
+            observer.observe(Event::GameOver { score: &score });
+            return game_state.outcome(score, table.face_down);
+        }
+        if !loops {
+            continue;
+        }
+        period += 1;
+
+        if game_state.same_position(&saved) {
+            let score = match game_state.player_out() {
+                Some(moves) => Score::LoseAfter(moves),
+                None => Score::Cycle {
+                    period,
+                    entered_at: cycle_entry(start, rules, strategy, period),
+                },
+            };
+            observer.observe(Event::GameOver { score: &score });
+            return game_state.outcome(score, table.face_down);
+        }
+
+        if period == power {
+            saved = game_state.clone();
+            power *= 2;
+            period = 0;
+        }
+    }
+}
+
+/// Find the move at which a game known to loop with the given period first
+/// enters the loop.
+fn cycle_entry<S: Strategy + ?Sized>(
+    start: GameState,
+    rules: &Rules,
+    strategy: &mut S,
+    period: usize,
+) -> usize {
+    let mut table = Table::default();
+    let mut advance = |game_state: &mut GameState| {
+        if game_state.play_trick(rules, &mut table, strategy, &mut NoObserver).is_some() {
+            unreachable!("game on a cycle cannot finish");
+        }
+    };
+
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        advance(&mut hare);
+    }
+    while !tortoise.same_position(&hare) {
+        advance(&mut tortoise);
+        advance(&mut hare);
+    }
+    tortoise.moves
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use {Comparison, DeckSource, Generator, PickupPolicy, Rank, WarDepth, Wild};
+    use Score::*;
+    use GameStepped::*;
+
+    fn deck(cards: &str) -> Deck {
+        cards.parse().unwrap()
+    }
+
+    #[test]
+    fn new_deals_from_sources() {
+        let rules = Rules {
+            copies_per_rank: 1,
+            players: 3,
+            computer_deck: DeckSource::Reversed,
+            player_deck: DeckSource::Sorted,
+            ..Rules::default()
+        };
+        let gs = GameState::new(&rules, &mut *Generator::Xoshiro256.seed(&[1]));
+        let sorted = deck("2 3 4 5 6 7 8 9 T J Q K A");
+
+        assert_eq!(gs.decks(), &[deck("A K Q J T 9 8 7 6 5 4 3 2"), sorted.clone(), sorted][..]);
+    }
+
+    #[test]
+    fn empty_computer() {
+        let gs = GameState::from_parts(deck(""), deck("2"), 0, 0);
+
+        assert_eq!(gs.step(&Rules::default()), Done(WinAfter(0)));
+    }
+
+    #[test]
+    fn empty_player() {
+        let gs = GameState::from_parts(deck("2"), deck(""), 0, 0);
+
+        assert_eq!(gs.step(&Rules::default()), Done(LoseAfter(0)));
+    }
+
+    #[test]
+    fn empty_tied_war() {
+        let gs = GameState::from_parts(deck("2 A A A 2"), deck("2 2 2 2 2"), 2, 0);
+
+        assert_eq!(gs.step(&Rules::default()), Done(TiedAt(2)));
+    }
+
+    #[test]
+    fn player_trick() {
+        let gs1 = GameState::from_parts(deck("2 3"), deck("4 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3"), deck("5 4 2"), 7, 0);
+
+        assert_eq!(gs1.step(&Rules::default()), Cont(gs2));
+    }
+
+    #[test]
+    fn computer_trick() {
+        let gs1 = GameState::from_parts(deck("4 5"), deck("2 3"), 6, 0);
+        let gs2 = GameState::from_parts(deck("5 4 2"), deck("3"), 7, 0);
+
+        assert_eq!(gs1.step(&Rules::default()), Cont(gs2));
+    }
+
+    fn comparison(comparison: Comparison) -> Rules {
+        Rules { comparison, ..Rules::default() }
+    }
+
+    #[test]
+    fn peace_player_trick() {
+        let gs1 = GameState::from_parts(deck("4 5"), deck("2 3"), 6, 0);
+        let gs2 = GameState::from_parts(deck("5"), deck("3 2 4"), 7, 0);
+
+        assert_eq!(gs1.step(&comparison(Comparison::Peace)), Cont(gs2));
+    }
+
+    #[test]
+    fn peace_computer_trick() {
+        let gs1 = GameState::from_parts(deck("2 3"), deck("4 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3 2 4"), deck("5"), 7, 0);
+
+        assert_eq!(gs1.step(&comparison(Comparison::Peace)), Cont(gs2));
+    }
+
+    #[test]
+    fn ace_low_player_trick() {
+        let gs1 = GameState::from_parts(deck("A 3"), deck("2 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3"), deck("5 2 A"), 7, 0);
+
+        assert_eq!(gs1.step(&comparison(Comparison::AceLow)), Cont(gs2));
+    }
+
+    #[test]
+    fn cyclic_player_trick() {
+        let gs1 = GameState::from_parts(deck("A 3"), deck("2 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3"), deck("5 2 A"), 7, 0);
+
+        assert_eq!(gs1.step(&comparison(Comparison::Cyclic)), Cont(gs2));
+    }
+
+    #[test]
+    fn cyclic_computer_trick() {
+        let gs1 = GameState::from_parts(deck("2 3"), deck("A 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3 2 A"), deck("5"), 7, 0);
+
+        assert_eq!(gs1.step(&comparison(Comparison::Cyclic)), Cont(gs2));
+    }
+
+    #[test]
+    fn suit_breaks_tie() {
+        let rules = Rules { suits_break_ties: true, ..Rules::default() };
+        let gs1 = GameState::from_parts(deck("7S 3"), deck("7C 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3 7S 7C"), deck("5"), 7, 0);
+
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn joker_beats_everything() {
+        let rules = Rules { comparison: Comparison::Peace, ..Rules::default() };
+        let gs1 = GameState::from_parts(deck("2 3"), deck("JK 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3"), deck("5 JK 2"), 7, 0);
+
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn wild_rank_beats_everything() {
+        let rules = Rules { wild_ranks: vec![Rank::Two], ..Rules::default() };
+        let gs1 = GameState::from_parts(deck("A 3"), deck("2 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3"), deck("5 2 A"), 7, 0);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+
+        // Two wild cards tie.
+        let gs1 = GameState::from_parts(deck("2 3"), deck("JK 5"), 6, 0);
+        assert_eq!(gs1.step(&rules), Done(TiedAt(6)));
+    }
+
+    #[test]
+    fn wild_card_starts_war() {
+        let rules = Rules { wild: Wild::StartsWar, ..Rules::default() };
+        let gs1 = GameState::from_parts(deck("9 2 3 4 K"), deck("JK 5 6 7 8"), 0, 0);
+        let gs2 = GameState::from_parts(deck("9 2 3 4 K JK 5 6 7 8"), deck(""), 1, 1);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+
+        // The joker only ties with the best card.
+        let rules = Rules { face_down_per_war: 1, ..rules };
+        let gs1 = GameState::with_players(vec![deck("JK 2 3"), deck("9 4 5"), deck("5 6 7")], 0);
+        let gs2 = GameState::from_seats(vec![deck(""), deck("9 4 5 5 JK 2 3"), deck("6 7")], Vec::new(), 1, 1);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn wild_card_starts_war_despite_suits() {
+        let rules = Rules {
+            wild: Wild::StartsWar,
+            suits_break_ties: true,
+            ..Rules::default()
+        };
+        let gs1 = GameState::from_parts(deck("JKC 2 3 4 K"), deck("9S 5 6 7 8"), 0, 0);
+        let gs2 = GameState::from_parts(deck("JKC 2 3 4 K 9S 5 6 7 8"), deck(""), 1, 1);
+
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn pickup_policy() {
+        let rules = Rules {
+            pickup: vec![PickupPolicy::WinnerFirst, PickupPolicy::LoserFirst],
+            ..Rules::default()
+        };
+        let gs1 = GameState::from_parts(deck("2 3"), deck("4 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3"), deck("5 2 4"), 7, 0);
+
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn strategy_arranges_won_cards() {
+        let gs1 = GameState::from_parts(deck("2 3"), deck("4 5"), 6, 0);
+        let gs2 = GameState::from_parts(deck("3"), deck("5 2 4"), 7, 0);
+        struct Reverse;
+        impl Strategy for Reverse {
+            fn arrange(&mut self, cards: &mut [Card], view: &View) {
+                assert_eq!((view.seat(), view.cards_held(PLAYER), view.cards_held(COMPUTER)), (PLAYER, 1, 1));
+                cards.reverse();
+            }
+        }
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![Box::new(NoStrategy), Box::new(Reverse)];
+
+        let stepped = gs1.step_with_strategy(&Rules::default(), &mut strategies[..], &mut NoObserver);
+        assert_eq!(stepped, Cont(gs2));
+    }
+
+    #[test]
+    fn random_pickup_does_not_cycle() {
+        let rules = Rules {
+            pickup: vec![PickupPolicy::Random(1)],
+            move_limit: 1000,
+            ..Rules::default()
+        };
+        let gs = GameState::from_parts(deck("7 3 8 K J"), deck("Q 6 2 5 T"), 3, 0);
+
+        assert!(!matches!(play_game(gs, &rules), Cycle { .. }));
+    }
+
+    #[test]
+    fn war() {
+        let gs1 = GameState::from_parts(deck("2 8 9 T J"), deck("2 3 4 5 6 7"), 8, 0);
+        let gs2 = GameState::from_parts(deck("2 8 9 T J 2 3 4 5 6"), deck("7"), 9, 1);
+
+        assert_eq!(gs1.step(&Rules::default()), Cont(gs2));
+    }
+
+    #[test]
+    fn war_events() {
+        let gs = GameState::from_parts(deck("2 8 9 T J"), deck("2 3 4 5 6"), 8, 0);
+
+        let mut events = Vec::new();
+        play_game_with(gs, &Rules::default(), &mut |event: Event| events.push(event.to_string()));
+        assert_eq!(
+            events,
+            vec![
+                "trick 9",
+                "  2C vs 2C",
+                "  war (depth 1, 3 face down)",
+                "  face down 8C vs 3C",
+                "  face down 9C vs 4C",
+                "  face down TC vs 5C",
+                "  JC vs 6C",
+                "  Computer collects 2C 8C 9C TC JC 2C 3C 4C 5C 6C (10 vs 0 cards)",
+                "trick 10",
+                "  Player is out",
+                "loss after 9 moves",
+            ]
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let gs = GameState::from_parts(deck("7 3 8 K J"), deck("Q 6 2 5 T"), 3, 0);
+
+        assert_eq!(play_game(gs, &Rules::default()), Cycle { period: 60, entered_at: 24 });
+    }
+
+    #[test]
+    fn finished_game_is_not_a_cycle() {
+        let gs = GameState::from_parts(deck("2 3"), deck("4 5"), 0, 0);
+
+        assert_eq!(play_game(gs, &Rules::default()), WinAfter(2));
+    }
+
+    #[test]
+    fn outcome() {
+        let gs = GameState::from_parts(deck("2 3 4 5 9"), deck("2 6 7 8 T J"), 0, 0);
+
+        assert_eq!(
+            play_game_outcome(gs, &Rules::default()),
+            Outcome {
+                score: WinAfter(1),
+                moves: 1,
+                wars: 1,
+                face_down: 6,
+                computer_cards: 0,
+                player_cards: 10,
+                standings: vec![vec![PLAYER], vec![COMPUTER]],
+            }
+        );
+    }
+
+    #[test]
+    fn short_war() {
+        let rules = Rules { face_down_per_war: 1, ..Rules::default() };
+        let gs1 = GameState::from_parts(deck("2 8 9"), deck("2 3 4 5"), 0, 0);
+        let gs2 = GameState::from_parts(deck("2 8 9 2 3 4"), deck("5"), 1, 1);
+
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn war_depth_by_rank() {
+        let rules = Rules { war_depth: "2=1".parse().unwrap(), ..Rules::default() };
+        let gs1 = GameState::from_parts(deck("2 8 9"), deck("2 3 4 5"), 0, 0);
+        let gs2 = GameState::from_parts(deck("2 8 9 2 3 4"), deck("5"), 1, 1);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+
+        // Other ranks lay the usual number of cards.
+        let gs1 = GameState::from_parts(deck("5 8 9 T J"), deck("5 3 4 6 7 Q"), 0, 0);
+        let gs2 = GameState::from_parts(deck("5 8 9 T J 5 3 4 6 7"), deck("Q"), 1, 1);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn war_depth_by_card_value() {
+        let rules = Rules { war_depth: WarDepth::CardValue, ..Rules::default() };
+        let gs1 = GameState::from_parts(deck("2 8 9 3"), deck("2 4 5 6 K"), 0, 0);
+        let gs2 = GameState::from_parts(deck(""), deck("K 2 4 5 6 2 8 9 3"), 1, 1);
+
+        let mut wars = Vec::new();
+        let stepped = gs1.step_with(&rules, &mut |event: Event| {
+            if let Event::War { depth, face_down } = event {
+                wars.push((depth, face_down));
+            }
+        });
+        assert_eq!(stepped, Cont(gs2));
+        assert_eq!(wars, vec![(1, 2)]);
+    }
+
+    fn exhaustion(policy: Exhaustion) -> Rules {
+        Rules { exhaustion: policy, ..Rules::default() }
+    }
+
+    #[test]
+    fn exhaustion_skip() {
+        let gs = GameState::from_parts(deck("5 8 9 T J K"), deck("5 3 4"), 4, 0);
+
+        assert_eq!(gs.step(&exhaustion(Exhaustion::Skip)), Done(LoseAfter(4)));
+    }
+
+    #[test]
+    fn exhaustion_last_card() {
+        let rules = exhaustion(Exhaustion::LastCard);
+        let gs1 = GameState::from_parts(deck("5 8 9 T J K"), deck("5 3 4"), 4, 0);
+        let gs2 = GameState::from_parts(deck("K 5 8 9 T J 5 3 4"), deck(""), 5, 1);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+
+        // With nothing left at all, the tied card is turned over again.
+        let gs1 = GameState::from_parts(deck("5 8 9 T 2"), deck("5"), 4, 0);
+        let gs2 = GameState::from_parts(deck(""), deck("5 5 8 9 T 2"), 5, 1);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+
+        // Unless nobody has a card to play against it.
+        let gs = GameState::from_parts(deck("2 A A A 2"), deck("2 2 2 2 2"), 2, 0);
+        assert_eq!(gs.step(&rules), Done(TiedAt(2)));
+    }
+
+    #[test]
+    fn exhaustion_lose_immediately() {
+        let rules = exhaustion(Exhaustion::LoseImmediately);
+        let gs = GameState::from_parts(deck("5 8 9 T J K"), deck("5 3 4"), 4, 0);
+        assert_eq!(gs.step(&rules), Done(LoseAfter(4)));
+
+        let gs1 = GameState::with_players(vec![deck("5 8 9 T J"), deck("5 3"), deck("2 4")], 0);
+        let gs2 = GameState::from_seats(
+            vec![deck("8 9 T J 5 5 3 2"), deck(""), deck("4")],
+            vec![(PLAYER, 0)],
+            1,
+            1,
+        );
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+
+        // Players who are all short lay what they can.
+        let gs = GameState::from_parts(deck("2 A A A 2"), deck("2 2 2 2 2"), 2, 0);
+        assert_eq!(gs.step(&rules), Done(TiedAt(2)));
+    }
+
+    #[test]
+    fn exhaustion_play_alone() {
+        let rules = exhaustion(Exhaustion::PlayAlone);
+        let gs1 = GameState::from_parts(deck("5 8 9 T J K"), deck("5 3 4"), 4, 0);
+        let gs2 = GameState::from_parts(deck("8 9 T J K 5 5"), deck("3 4"), 5, 1);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+
+        let gs1 = GameState::with_players(vec![deck("5 8 9 T J"), deck("5 3"), deck("2 4")], 0);
+        let gs2 = GameState::from_seats(vec![deck("8 9 T J 5 5 2"), deck("3"), deck("4")], Vec::new(), 1, 1);
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn exhaustion_reshuffle() {
+        let rules = Rules {
+            face_down_per_war: 1,
+            ..exhaustion(Exhaustion::Reshuffle(3))
+        };
+        let gs = GameState::from_parts(deck("5 2 7 9 A"), deck("5 3 7 4"), 0, 0);
+
+        let mut events = Vec::new();
+        let stepped = gs.clone().step_with(&rules, &mut |event: Event| events.push(event.to_string()));
+        assert_eq!(events.iter().filter(|event| *event == "  war reshuffled").count(), 1);
+        assert_eq!(gs.clone().step(&rules), stepped);
+        match stepped {
+            Cont(gs) => assert_eq!(gs.computer().len() + gs.player().len(), 9),
+            Done(score) => panic!("unexpected {:?}", score),
+        }
+        assert!(!rules.is_deterministic());
+    }
+
+    #[test]
+    fn three_player_trick() {
+        let gs1 = GameState::with_players(vec![deck("4 2"), deck("9 3"), deck("7 5")], 0);
+        let gs2 = GameState::with_players(vec![deck("2"), deck("3 9 7 4"), deck("5")], 1);
+
+        assert_eq!(gs1.step(&Rules::default()), Cont(gs2));
+    }
+
+    #[test]
+    fn war_between_tied_players() {
+        let rules = Rules { face_down_per_war: 1, ..Rules::default() };
+        let gs1 = GameState::with_players(vec![deck("9 2 3"), deck("4 5"), deck("9 6 K")], 0);
+        let gs2 = GameState::from_seats(
+            vec![deck(""), deck("5"), deck("9 6 K 9 2 3 4")],
+            Vec::new(),
+            1,
+            1,
+        );
+
+        assert_eq!(gs1.step(&rules), Cont(gs2));
+    }
+
+    #[test]
+    fn elimination_order() {
+        let gs = GameState::with_players(vec![deck("2"), deck("3"), deck("5 A"), deck("6 7")], 0);
+        let outcome = play_game_outcome(gs, &Rules::default());
+
+        assert_eq!(outcome.score, LoseAfter(1));
+        assert_eq!(outcome.standings, vec![vec![2], vec![3], vec![COMPUTER, PLAYER]]);
+    }
+
+    #[test]
+    fn move_limit() {
+        let rules = Rules { move_limit: 10, ..Rules::default() };
+        let gs = GameState::from_parts(deck("2"), deck("3 4"), 10, 0);
+
+        assert_eq!(gs.step(&rules), Done(FinishWith(2)));
+    }
+}
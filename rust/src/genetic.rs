@@ -0,0 +1,225 @@
+use std::cmp::Ordering;
+
+use rayon::prelude::*;
+
+use {Candidate, Card, Deck, Mutation, Rules, Score, WarRng};
+
+/// Settings for `evolve`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Genetic {
+    /// Number of decks in each generation.
+    pub population: usize,
+    /// Number of best decks copied unchanged into the next generation.
+    pub elitism: usize,
+    /// Number of generations bred after the initial random population.
+    pub generations: usize,
+    /// Probability that a child receives a random `Mutation`.
+    pub mutation_rate: f64,
+    /// Number of decks competing for each parent slot.
+    pub tournament_size: usize,
+}
+
+impl Default for Genetic {
+    fn default() -> Self {
+        Genetic {
+            population: 50,
+            elitism: 2,
+            generations: 20,
+            mutation_rate: 0.5,
+            tournament_size: 3,
+        }
+    }
+}
+
+/// Fitness summary of one generation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generation {
+    /// 0 for the initial random population.
+    pub number: usize,
+    pub best: Score,
+    /// Mean of `Score::to_int` over the population.
+    pub mean: f64,
+}
+
+/// Order crossover (OX) for decks holding repeated cards.
+///
+/// The child keeps `first[start..end]` in place and fills the remaining
+/// positions, starting at `end` and wrapping around, with the cards of
+/// `second` in the order they appear from `end` on. Cards are taken from
+/// `second` only while the child still needs copies of them, so the child
+/// always holds exactly the same cards as its parents.
+pub fn order_crossover(first: &[Card], second: &[Card], start: usize, end: usize) -> Vec<Card> {
+    let len = first.len();
+    assert_eq!(len, second.len());
+    assert!(start <= end && end <= len);
+
+    let mut needed = [0usize; 64];
+    for &card in first {
+        needed[card.to_byte() as usize] += 1;
+    }
+    for &card in &first[start..end] {
+        needed[card.to_byte() as usize] -= 1;
+    }
+
+    let mut child = first.to_vec();
+    let mut fill = (end..len).chain(0..start);
+    for offset in 0..len {
+        let card = second[(end + offset) % len];
+        let count = &mut needed[card.to_byte() as usize];
+        if *count > 0 {
+            *count -= 1;
+            child[fill.next().expect("parents hold different cards")] = card;
+        }
+    }
+    child
+}
+
+/// Search for a strong player deck against `computer` with a genetic
+/// algorithm.
+///
+/// The initial population is made of shuffled half decks. Each following
+/// generation keeps the `elitism` best decks and breeds the rest from
+/// tournament-selected parents with `order_crossover` and `Mutation`.
+/// Decks are ranked by their `Score`. `on_generation` is called after every
+/// generation is evaluated, and the best deck ever seen is returned.
+pub fn evolve<R, F>(
+    computer: &Deck,
+    rules: &Rules,
+    config: &Genetic,
+    rng: &mut R,
+    mut on_generation: F,
+) -> Candidate
+where
+    R: WarRng + ?Sized,
+    F: FnMut(&Generation),
+{
+    assert!(config.population > 0, "population must not be empty");
+    let decks = (0..config.population)
+        .map(|_| Deck::new_shuffle(rules, rng))
+        .collect();
+    let mut population = evaluate(computer, decks, rules);
+    let mut best = population[0].clone();
+
+    for number in 0..config.generations + 1 {
+        if number > 0 {
+            let decks = breed(&population, config, rng);
+            population = evaluate(computer, decks, rules);
+        }
+        if population[0].score.compare(&best.score, rules) == Ordering::Greater {
+            best = population[0].clone();
+        }
+        let total: usize = population.iter().map(|candidate| candidate.value).sum();
+        on_generation(&Generation {
+            number,
+            best: population[0].score.clone(),
+            mean: total as f64 / population.len() as f64,
+        });
+    }
+    best
+}
+
+/// Play every deck, returning the results best first.
+fn evaluate(computer: &Deck, decks: Vec<Deck>, rules: &Rules) -> Vec<Candidate> {
+    let mut population: Vec<Candidate> = decks
+        .into_par_iter()
+        .map(|deck| Candidate::evaluate(computer, deck, rules))
+        .collect();
+    population.sort_by(|a, b| b.score.compare(&a.score, rules));
+    population
+}
+
+fn breed<R>(population: &[Candidate], config: &Genetic, rng: &mut R) -> Vec<Deck>
+where
+    R: WarRng + ?Sized,
+{
+    let mut next: Vec<Deck> = population
+        .iter()
+        .take(config.elitism)
+        .map(|candidate| candidate.deck.clone())
+        .collect();
+
+    while next.len() < config.population {
+        let first = tournament(population, config.tournament_size, rng).deck.to_vec();
+        let second = tournament(population, config.tournament_size, rng).deck.to_vec();
+        let len = first.len();
+        let start = rng.gen_range(0, len + 1);
+        let end = rng.gen_range(start, len + 1);
+        let mut child = order_crossover(&first, &second, start, end);
+        if len >= 2 && rng.next_f64() < config.mutation_rate {
+            Mutation::random(len, rng).apply(&mut child);
+        }
+        next.push(Deck::from_vec(child));
+    }
+    next
+}
+
+fn tournament<'a, R>(population: &'a [Candidate], size: usize, rng: &mut R) -> &'a Candidate
+where
+    R: WarRng + ?Sized,
+{
+    // The population is sorted best first, so the lowest index wins.
+    let winner = (0..size.max(1))
+        .map(|_| rng.gen_range(0, population.len()))
+        .min()
+        .unwrap_or(0);
+    &population[winner]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Xoshiro256StarStar;
+
+    fn cards(cards: &str) -> Vec<Card> {
+        cards.parse::<Deck>().unwrap().to_vec()
+    }
+
+    #[test]
+    fn crossover_distinct_cards() {
+        let first = cards("2 3 4 5 6 7 8 9");
+        let second = cards("9 7 5 3 2 4 6 8");
+
+        assert_eq!(order_crossover(&first, &second, 2, 5), cards("3 2 4 5 6 8 9 7"));
+        assert_eq!(order_crossover(&first, &second, 0, 0), second);
+        assert_eq!(order_crossover(&first, &second, 0, 8), first);
+    }
+
+    #[test]
+    fn crossover_repeated_cards() {
+        let first = cards("2 2 3 3 4 4");
+        let second = cards("4 3 2 4 3 2");
+
+        let child = order_crossover(&first, &second, 1, 3);
+        assert_eq!(child, cards("4 2 3 4 3 2"));
+    }
+
+    #[test]
+    fn reproducible_generations() {
+        let rules = Rules {
+            copies_per_rank: 2,
+            move_limit: 2000,
+            ..Rules::default()
+        };
+        let computer = Deck::new_half_deck(&rules);
+        let config = Genetic {
+            population: 10,
+            generations: 5,
+            ..Genetic::default()
+        };
+        let run = || {
+            let mut rng = Xoshiro256StarStar::from_seed(&[3]);
+            let mut generations = Vec::new();
+            let best = evolve(&computer, &rules, &config, &mut rng, |g| generations.push(g.clone()));
+            (best, generations)
+        };
+
+        let (best, generations) = run();
+        assert_eq!(generations.len(), 6);
+        for pair in generations.windows(2) {
+            // Elitism means the best deck is never lost.
+            assert_ne!(pair[1].best.compare(&pair[0].best, &rules), Ordering::Less);
+        }
+        assert_eq!(best.score, generations[5].best);
+        assert_eq!((best, generations), run());
+    }
+}
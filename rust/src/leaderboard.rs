@@ -0,0 +1,75 @@
+use std::cmp::Ordering;
+
+use {Rules, Score};
+
+/// A seed and the score it produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub seed: usize,
+    pub score: Score,
+}
+
+/// The best `capacity` seeds seen so far, best first.
+///
+/// Entries are ranked by `Score::compare` under the rules the seeds were
+/// played by, with ties going to the lower seed.
+#[derive(Debug, Clone)]
+pub struct Leaderboard {
+    capacity: usize,
+    rules: Rules,
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    /// An empty leaderboard keeping `capacity` seeds played under `rules`.
+    pub fn new(capacity: usize, rules: &Rules) -> Self {
+        Leaderboard {
+            capacity,
+            rules: rules.clone(),
+            entries: Vec::with_capacity(capacity + 1),
+        }
+    }
+
+    /// Offer a result to the leaderboard. Returns the rank (starting at 0)
+    /// it was placed at, or `None` if it did not make the cut.
+    pub fn insert(&mut self, seed: usize, score: Score) -> Option<usize> {
+        let rules = &self.rules;
+        let rank = self.entries.iter().position(|entry| {
+            score.compare(&entry.score, rules).then(entry.seed.cmp(&seed)) == Ordering::Greater
+        });
+        let rank = match rank {
+            Some(rank) => rank,
+            None if self.entries.len() < self.capacity => self.entries.len(),
+            None => return None,
+        };
+        self.entries.insert(rank, Entry { seed, score });
+        self.entries.truncate(self.capacity);
+        Some(rank)
+    }
+
+    /// The seeds kept so far, best first.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Score::*;
+
+    #[test]
+    fn keeps_best_with_ties_by_seed() {
+        let mut board = Leaderboard::new(3, &Rules::default());
+
+        assert_eq!(board.insert(5, LoseAfter(10)), Some(0));
+        assert_eq!(board.insert(9, WinAfter(100)), Some(0));
+        assert_eq!(board.insert(7, WinAfter(100)), Some(0));
+        assert_eq!(board.insert(8, WinAfter(100)), Some(1));
+        assert_eq!(board.insert(2, LoseAfter(10)), None);
+        assert_eq!(board.insert(1, FinishWith(20)), None);
+
+        let seeds: Vec<_> = board.entries().iter().map(|entry| entry.seed).collect();
+        assert_eq!(seeds, vec![7, 8, 9]);
+    }
+}
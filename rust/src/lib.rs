@@ -0,0 +1,73 @@
+//! A simulation engine for the War card game.
+//!
+//! A game is played between a fixed, sorted computer deck and a shuffled
+//! player deck, each holding `Card`s made of a `Rank` and a `Suit`, joined
+//! by as many further shuffled decks as `Rules::players` asks for. A
+//! `DeckSource` in the rules deals either side some other way. Drive a game
+//! one trick at a time with `GameState::step`, or run it to completion with
+//! `play_game`. Deck sizes, the move limit and the size of wars are
+//! controlled by `Rules`. Large numbers of seeds can be played in parallel
+//! with `sweep`, and the best of them collected in a `Leaderboard`. Rather
+//! than relying on lucky shuffles, `local_search` optimizes a player deck
+//! directly, and `evolve` breeds a population of them. To watch a game
+//! unfold card by card, pass an `Observer` to `play_game_with`, and to save
+//! a game for later, `Replay::record` it. A game in progress can be saved
+//! and restored with `GameState::to_text` and `GameState::from_text`, or
+//! their binary counterparts. Players can arrange the cards they win with a
+//! `Strategy`, and strategies can be played against each other with
+//! `tournament`.
+
+#[cfg(test)]
+extern crate rand;
+extern crate rayon;
+
+mod card;
+mod compare;
+mod deck;
+mod game;
+mod genetic;
+mod leaderboard;
+mod observer;
+mod optimize;
+mod output;
+mod pickup;
+mod replay;
+mod rules;
+mod rng;
+mod score;
+mod snapshot;
+mod source;
+mod stats;
+mod strategy;
+mod sweep;
+mod tournament;
+
+pub use card::{Card, Rank, Suit};
+pub use compare::{Comparison, Wild};
+pub use deck::{Deck, Shuffle};
+pub use game::{
+    play_game, play_game_outcome, play_game_with, play_game_with_strategy, GameState, GameStepped, Outcome,
+    COMPUTER, PLAYER,
+};
+pub use observer::{seat_name, Event, NoObserver, Observer};
+pub use genetic::{evolve, order_crossover, Generation, Genetic};
+pub use leaderboard::{Entry, Leaderboard};
+pub use optimize::{local_search, Candidate, LocalSearch, Mutation};
+pub use pickup::PickupPolicy;
+pub use rules::{Exhaustion, Rules, WarDepth};
+pub use output::Format;
+pub use replay::{Replay, ReplayError, Trick, REPLAY_VERSION};
+pub use rng::{Generator, Isaac64, WarRng, Xoshiro256StarStar};
+pub use score::Score;
+pub use snapshot::{SnapshotError, SNAPSHOT_VERSION};
+pub use source::DeckSource;
+pub use stats::{Stats, Tally};
+pub use strategy::{Greedy, KeepAcesBack, NoStrategy, RandomOrder, Strategy, StrategyKind, View};
+pub use sweep::{play_seed, sweep, SeedRange};
+pub use tournament::{tournament, Record, Results, Tournament};
+
+/// Default for `Rules::move_limit`.
+pub const MAX_MOVES: usize = 1000000;
+
+/// Default for `Rules::copies_per_rank`.
+pub const SUITS_PER_PLAYER: usize = 256;
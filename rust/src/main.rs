@@ -1,255 +1,572 @@
-extern crate rand;
-
-use rand::{Rng, SeedableRng, StdRng};
-use std::cmp::{Ord, Ordering};
-use std::collections::VecDeque;
-
-const MAX_MOVES: usize = 1000000;
-const SUITS_PER_PLAYER: usize = 256;
-
-#[derive(Debug, PartialEq)]
-struct Deck(VecDeque<u8>);
-impl Deck {
-    fn new_half_deck() -> Self {
-        let mut deck = VecDeque::new();
-        for _ in 0..SUITS_PER_PLAYER {
-            for i in 2..15 {
-                deck.push_back(i);
-            }
+extern crate clap;
+extern crate rayon;
+extern crate war;
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::process;
+
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use war::{
+    evolve, local_search, seat_name, sweep, tournament, Candidate, Comparison, Deck, DeckSource,
+    Event, Exhaustion, Format, GameState, GameStepped, Generator, Genetic, Leaderboard, LocalSearch,
+    PickupPolicy, Rank, Replay, Rules, SeedRange, Stats, StrategyKind, Tournament, WarDepth, Wild,
+};
+
+/// Simulate games of War between a sorted computer deck and a shuffled
+/// player deck, and search for player decks that win quickly.
+#[derive(Parser)]
+#[command(name = "war-rust")]
+struct Cli {
+    #[command(flatten)]
+    rules: RulesArgs,
+
+    /// Number of worker threads [default: one per core]
+    #[arg(long, global = true)]
+    threads: Option<usize>,
+
+    /// Random number generator used to expand seeds: rand03, which
+    /// reproduces results recorded with rand 0.3, or xoshiro256
+    #[arg(long, global = true, default_value_t = Generator::Rand03)]
+    rng: Generator,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct RulesArgs {
+    /// Tricks played before a game is cut off
+    #[arg(long, global = true, default_value_t = war::MAX_MOVES)]
+    move_limit: usize,
+
+    /// Copies of each rank dealt to each player
+    #[arg(long, global = true, default_value_t = war::SUITS_PER_PLAYER)]
+    copies: usize,
+
+    /// Comma separated ranks in play, lowest first [default: 2 to A]
+    #[arg(long, global = true, value_delimiter = ',')]
+    ranks: Vec<Rank>,
+
+    /// Jokers dealt to each player on top of the ranks in play
+    #[arg(long, global = true, default_value_t = 0)]
+    jokers: usize,
+
+    /// Comma separated ranks whose cards are wild, like jokers
+    #[arg(long, global = true, value_delimiter = ',')]
+    wild_ranks: Vec<Rank>,
+
+    /// What a wild card does: beats (every card that is not wild) or war
+    /// (ties with the best card turned over)
+    #[arg(long, global = true, default_value_t = Wild::Beats)]
+    wild: Wild,
+
+    /// How cards are ranked in a trick: standard, peace (lowest wins),
+    /// ace-low or cyclic (the lowest rank beats the highest)
+    #[arg(long, global = true, default_value_t = Comparison::Standard)]
+    comparison: Comparison,
+
+    /// Break ties between cards of equal rank by suit, spades highest
+    #[arg(long, global = true)]
+    suits_break_ties: bool,
+
+    /// Cards each player lays face down in a war
+    #[arg(long, global = true, default_value_t = 3)]
+    face_down: usize,
+
+    /// Cards laid face down by the tied rank: fixed for --face-down,
+    /// card-value for the blackjack value, or RANK=COUNT pairs such as
+    /// 7=7,J=10 with --face-down for the other ranks
+    #[arg(long, global = true, default_value_t = WarDepth::Fixed)]
+    war_depth: WarDepth,
+
+    /// Players at the table, counting the computer; extra players are
+    /// dealt their own decks like the player's
+    #[arg(long, global = true, default_value_t = 2)]
+    players: usize,
+
+    /// Where the computer deck comes from: sorted, reverse, high-low,
+    /// by-rank, dealt (shuffled from the seed), shuffled:SEED, cards:DECK or
+    /// file:PATH for a deck in card notation
+    #[arg(long, global = true, default_value = "sorted", value_parser = deck_source)]
+    computer_deck: DeckSource,
+
+    /// Where the player deck, and those of any extra players, come from, as
+    /// for --computer-deck
+    #[arg(long, global = true, default_value = "dealt", value_parser = deck_source)]
+    player_deck: DeckSource,
+
+    /// Comma separated pickup policy of each seat, computer first:
+    /// winner-first, loser-first, interleaved, high-first, low-first or
+    /// random:SEED [default: winner-first]
+    #[arg(long, global = true, value_delimiter = ',')]
+    pickup: Vec<PickupPolicy>,
+
+    /// What happens to a player too short of cards to finish a war: skip,
+    /// last-card, lose, play-alone or reshuffle:SEED
+    #[arg(long, global = true, default_value_t = Exhaustion::Skip)]
+    exhaustion: Exhaustion,
+}
+
+impl RulesArgs {
+    fn rules(&self) -> Rules {
+        let mut rules = Rules {
+            move_limit: self.move_limit,
+            copies_per_rank: self.copies,
+            jokers: self.jokers,
+            wild_ranks: self.wild_ranks.clone(),
+            wild: self.wild,
+            comparison: self.comparison,
+            suits_break_ties: self.suits_break_ties,
+            face_down_per_war: self.face_down,
+            war_depth: self.war_depth.clone(),
+            players: self.players,
+            computer_deck: self.computer_deck.clone(),
+            player_deck: self.player_deck.clone(),
+            pickup: self.pickup.clone(),
+            exhaustion: self.exhaustion,
+            ..Rules::default()
+        };
+        if !self.ranks.is_empty() {
+            rules.ranks = self.ranks.clone();
         }
-        Deck(deck)
+        rules
     }
+}
 
-    fn new_shuffle(rng: &mut StdRng) -> Self {
-        let mut deck = Self::new_half_deck();
-        rng.shuffle(deck.0.as_mut_slices().0);
-        deck
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// Play a single game, dealt from a seed or from explicit decks
+    Play(PlayArgs),
+    /// Play every seed in a range and print each result
+    Sweep {
+        #[command(flatten)]
+        seeds: SeedArgs,
+
+        /// Output format: text, jsonl or csv
+        #[arg(long, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Search for the best player deck
+    #[command(subcommand)]
+    Search(Search),
+    /// Play every seed in a range and summarize the results
+    Stats(SeedArgs),
+    /// Play a recorded game again, checking that it goes as recorded. The
+    /// rules are taken from the file.
+    Replay {
+        /// File written by `play --record`
+        file: PathBuf,
+    },
+    /// Play strategies for arranging won cards against each other
+    Tournament {
+        /// Comma separated strategies: none, greedy, keep-aces-back or
+        /// random:SEED
+        #[arg(long, value_delimiter = ',', required = true)]
+        strategies: Vec<StrategyKind>,
+
+        /// Deals played by each pair of strategies, once with each on
+        /// either deck
+        #[arg(long, default_value_t = 100)]
+        deals: usize,
+
+        /// Seed word placed before the deal number to shuffle both decks
+        #[arg(long, default_value_t = 1)]
+        seed: usize,
+    },
+}
 
-    fn new_empty() -> Self {
-        Deck(VecDeque::new())
-    }
+#[derive(Args)]
+struct PlayArgs {
+    /// Seed words used to shuffle the player deck
+    #[arg(long, num_args = 1.., conflicts_with_all = ["computer", "player", "resume"])]
+    seed: Vec<usize>,
 
-    #[cfg(test)]
-    fn from_vec(vec: Vec<u8>) -> Self {
-        Deck(From::from(vec))
-    }
+    /// Continue a game from a snapshot written with --save
+    #[arg(long, conflicts_with_all = ["computer", "player"])]
+    resume: Option<PathBuf>,
 
-    fn draw(&mut self) -> Option<u8> {
-        self.0.pop_front()
-    }
+    /// Computer deck in card notation, top card first, such as
+    /// "2 3 T J Q K A" or "[2 3 4 5 6 7 8 9 T J Q K A]*256"
+    #[arg(long, requires = "player")]
+    computer: Option<Deck>,
 
-    fn add(&mut self, card: u8) {
-        self.0.push_back(card);
-    }
+    /// Player deck in card notation, top card first
+    #[arg(long, requires = "computer")]
+    player: Option<Deck>,
 
-    fn add_pile(&mut self, pile: Deck) {
-        for x in pile.0 {
-            self.add(x);
-        }
-    }
-}
+    /// Output format: text, jsonl or csv
+    #[arg(long, default_value_t = Format::Text)]
+    format: Format,
 
-#[derive(Debug, PartialEq)]
-enum GameStepped {
-    Cont(GameState),
-    Done(Score),
-}
+    /// Print every card played, to standard error
+    #[arg(long)]
+    trace: bool,
 
-#[derive(Debug, PartialEq)]
-struct GameState {
-    computer: Deck,
-    player: Deck,
-    moves: usize,
-}
-impl GameState {
-    fn new(mut rng: &mut StdRng) -> Self {
-        GameState {
-            computer: Deck::new_half_deck(),
-            player: Deck::new_shuffle(&mut rng),
-            moves: 0,
-        }
-    }
+    /// Save the game to a replay file
+    #[arg(long)]
+    record: Option<PathBuf>,
 
-    fn step(mut self) -> GameStepped {
-        use Score::*;
-        use GameStepped::*;
-        if self.moves >= MAX_MOVES {
-            assert!(self.moves == MAX_MOVES);
-            return Done(FinishWith(self.player.0.len()));
-        }
+    /// Include every trick in the replay file
+    #[arg(long, requires = "record")]
+    tricks: bool,
 
-        let mut computer_pile = Deck::new_empty();
-        let mut player_pile = Deck::new_empty();
-
-
-        loop {
-            let (computer, player) =
-                match (self.computer.draw(), self.player.draw()) {
-                    (None, None) => return Done(TiedAt(self.moves)),
-                    (None, Some(_)) => return Done(WinAfter(self.moves)),
-                    (Some(_), None) => return Done(LoseAfter(self.moves)),
-                    (Some(x), Some(y)) => (x, y)
-                };
-
-            computer_pile.add(computer);
-            player_pile.add(player);
-
-            match computer.cmp(&player) {
-                // player wins
-                Ordering::Less => {
-                    self.player.add_pile(player_pile);
-                    self.player.add_pile(computer_pile);
-                    self.moves += 1;
-                    return Cont(self);
-                }
+    /// Stop once this many tricks have been played and save a snapshot
+    #[arg(long, requires = "save", conflicts_with = "record")]
+    pause_at: Option<usize>,
 
-                // computer wins
-                Ordering::Greater => {
-                    self.computer.add_pile(computer_pile);
-                    self.computer.add_pile(player_pile);
-                    self.moves += 1;
-                    return Cont(self);
-                }
+    /// File the snapshot is saved to
+    #[arg(long, requires = "pause_at")]
+    save: Option<PathBuf>,
 
-                Ordering::Equal => {
-                    for _ in 1..4 {
-                        match self.computer.draw() {
-                            None => (),
-                            Some(x) => computer_pile.add(x),
-                        }
-                        match self.player.draw() {
-                            None => (),
-                            Some(x) => player_pile.add(x),
-                        }
-                    }
-                }
-            }
-        }
-    }
+    /// Save the snapshot in the compact binary form instead of as text
+    #[arg(long, requires = "save")]
+    binary: bool,
 }
 
-#[derive(Debug, PartialEq)]
-enum Score {
-    WinAfter(usize),
-    LoseAfter(usize),
-    FinishWith(usize),
-    TiedAt(usize),
+#[derive(Args)]
+struct SeedArgs {
+    /// First counter to play
+    #[arg(default_value_t = 1)]
+    start: usize,
+
+    /// Counter to stop before
+    #[arg(default_value_t = 1001)]
+    end: usize,
+
+    /// Seed words placed before the counter to form multi-word seeds
+    #[arg(long, num_args = 1..)]
+    prefix: Vec<usize>,
 }
 
-impl Score {
-    fn to_int(&self) -> usize {
-        match self {
-            &Score::LoseAfter(moves) => moves,
-            &Score::TiedAt(_moves) => MAX_MOVES + (13 * SUITS_PER_PLAYER),
-            &Score::FinishWith(cards) => MAX_MOVES + cards,
-            &Score::WinAfter(moves) => MAX_MOVES + (13 * SUITS_PER_PLAYER * 2) + (MAX_MOVES - moves),
+impl SeedArgs {
+    fn seeds(&self, generator: Generator) -> SeedRange {
+        SeedRange {
+            prefix: self.prefix.clone(),
+            counters: self.start..self.end,
+            generator,
         }
     }
 }
 
-fn play_game(mut game_state: GameState) -> Score {
-    loop {
-        game_state = match game_state.step() {
-            GameStepped::Cont(game_state) => game_state,
-            GameStepped::Done(score) => return score,
-        }
-    }
+#[derive(Subcommand)]
+enum Search {
+    /// Play a range of seeds, keeping the best N
+    Seeds {
+        #[command(flatten)]
+        seeds: SeedArgs,
+
+        /// Size of the leaderboard
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Improve one shuffled deck by hill climbing or simulated annealing
+    Local {
+        /// Seed for the starting deck and the search
+        #[arg(long, default_value_t = 1)]
+        seed: usize,
+
+        #[arg(long, default_value_t = 1000)]
+        iterations: usize,
+
+        /// Initial annealing temperature; 0 gives plain hill climbing
+        #[arg(long, default_value_t = 0.0)]
+        start_temperature: f64,
+
+        #[arg(long, default_value_t = 0.0)]
+        end_temperature: f64,
+    },
+    /// Breed player decks with a genetic algorithm
+    Genetic {
+        /// Seed for the initial population and the search
+        #[arg(long, default_value_t = 1)]
+        seed: usize,
+
+        #[arg(long, default_value_t = 20)]
+        generations: usize,
+
+        #[arg(long, default_value_t = 50)]
+        population: usize,
+
+        /// Best decks carried unchanged into each generation
+        #[arg(long, default_value_t = 2)]
+        elitism: usize,
+
+        #[arg(long, default_value_t = 0.5)]
+        mutation_rate: f64,
+
+        #[arg(long, default_value_t = 3)]
+        tournament_size: usize,
+    },
 }
 
-fn main() {
-    for x in 1..1001 {
-        let seed: &[_] = &[x];
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
-        let score = play_game(GameState::new(&mut rng));
-        println!("{}: {} ({:?})", x, score.to_int(), score);
+/// The words of `seed` as `--seed` takes them.
+fn seed_words(seed: &[usize]) -> String {
+    let words: Vec<String> = seed.iter().map(|word| word.to_string()).collect();
+    words.join(" ")
+}
+
+/// Parse a `DeckSource`, reading `file:PATH` into `DeckSource::Cards`.
+fn deck_source(s: &str) -> Result<DeckSource, String> {
+    match s.strip_prefix("file:") {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|error| format!("cannot read {}: {}", path, error))?
+            .trim()
+            .parse()
+            .map(DeckSource::Cards),
+        None => s.parse(),
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use Score::*;
-    use GameStepped::*;
+/// Report invalid input the same way clap does, exiting with status 2.
+fn invalid(message: &str) -> ! {
+    Cli::command().error(ErrorKind::ValueValidation, message).exit()
+}
 
-    #[test]
-    fn empty_computer() {
-        let gs = GameState {
-            computer: Deck::from_vec(vec![]),
-            player: Deck::from_vec(vec![2]),
-            moves: 0,
-        };
+/// Report a failure that is not a usage error, exiting with status 1.
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1)
+}
 
-        assert_eq!(gs.step(), Done(WinAfter(0)));
-    }
+/// Restore a snapshot in either form.
+fn load_snapshot(bytes: &[u8], rules: &Rules) -> Result<GameState, String> {
+    let game = if bytes.starts_with(b"WARS") {
+        GameState::from_bytes(bytes, rules)
+    } else {
+        let text = String::from_utf8_lossy(bytes);
+        GameState::from_text(&text, rules)
+    };
+    game.map_err(|error| error.to_string())
+}
 
-    #[test]
-    fn empty_player() {
-        let gs = GameState {
-            computer: Deck::from_vec(vec![2]),
-            player: Deck::from_vec(vec![]),
-            moves: 0,
-        };
+fn print_best(best: &Candidate) {
+    println!();
+    println!("best: {}", best.score);
+    println!("{}", best.deck);
+}
 
-        assert_eq!(gs.step(), Done(LoseAfter(0)));
+/// Print the finishing order, one place per line, with tied seats on the
+/// same line.
+fn print_standings(standings: &[Vec<usize>]) {
+    let mut place = 1;
+    for seats in standings {
+        let names: Vec<String> = seats.iter().map(|&seat| seat_name(seat)).collect();
+        println!("{}. {}", place, names.join(", "));
+        place += seats.len();
     }
+}
 
-    #[test]
-    fn empty_tied_war() {
-        let gs = GameState {
-            computer: Deck::from_vec(vec![2, 14, 14, 14, 2]),
-            player: Deck::from_vec(vec![2, 2, 2, 2, 2]),
-            moves: 2,
-        };
-
-        assert_eq!(gs.step(), Done(TiedAt(2)));
+fn check_seeds(args: &SeedArgs) {
+    if args.start > args.end {
+        invalid("START must not be greater than END");
     }
+}
 
-    #[test]
-    fn player_trick() {
-        let gs1 = GameState {
-            computer: Deck::from_vec(vec![2, 3]),
-            player: Deck::from_vec(vec![4, 5]),
-            moves: 6,
-        };
-        let gs2 = GameState {
-            computer: Deck::from_vec(vec![3]),
-            player: Deck::from_vec(vec![5, 4, 2]),
-            moves: 7,
-        };
-
-        assert_eq!(gs1.step(), Cont(gs2));
+fn main() {
+    let cli = Cli::parse();
+    let rules = cli.rules.rules();
+    if rules.cards_per_player() == 0 {
+        invalid("each player needs at least one card");
     }
-
-    #[test]
-    fn computer_trick() {
-        let gs1 = GameState {
-            player: Deck::from_vec(vec![2, 3]),
-            computer: Deck::from_vec(vec![4, 5]),
-            moves: 6,
-        };
-        let gs2 = GameState {
-            player: Deck::from_vec(vec![3]),
-            computer: Deck::from_vec(vec![5, 4, 2]),
-            moves: 7,
-        };
-
-        assert_eq!(gs1.step(), Cont(gs2));
+    if rules.players < 2 {
+        invalid("--players must be at least 2");
+    }
+    if rules.pickup.len() > rules.players {
+        invalid("--pickup lists more seats than there are players");
+    }
+    for (source, flag) in &[(&rules.computer_deck, "--computer-deck"), (&rules.player_deck, "--player-deck")] {
+        if let Err(error) = source.check(&rules) {
+            invalid(&format!("{}: {}", flag, error));
+        }
+    }
+    if let Some(threads) = cli.threads {
+        if threads == 0 {
+            invalid("--threads must be at least 1");
+        }
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .expect("thread pool already started");
     }
 
-    #[test]
-    fn war() {
-        let gs1 = GameState {
-            player: Deck::from_vec(vec![2, 3, 4, 5, 6, 7]),
-            computer: Deck::from_vec(vec![2, 8, 9, 10, 11]),
-            moves: 8,
-        };
-        let gs2 = GameState {
-            player: Deck::from_vec(vec![7]),
-            computer: Deck::from_vec(vec![2, 8, 9, 10, 11, 2, 3, 4, 5, 6]),
-            moves: 9,
-        };
-
-        assert_eq!(gs1.step(), Cont(gs2));
+    match cli.command {
+        Command::Play(args) => {
+            let (seed, game) = if let Some(path) = args.resume {
+                let game = fs::read(&path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|bytes| load_snapshot(&bytes, &rules))
+                    .unwrap_or_else(|error| fail(&format!("cannot resume {}: {}", path.display(), error)));
+                (Vec::new(), game)
+            } else if let (Some(computer), Some(player)) = (args.computer, args.player) {
+                if rules.players != 2 {
+                    invalid("--computer and --player need a two-player game");
+                }
+                (Vec::new(), GameState::from_decks(computer, player, 0))
+            } else {
+                let seed = if args.seed.is_empty() { vec![1] } else { args.seed };
+                let game = GameState::new(&rules, &mut cli.rng.seed(&seed));
+                (seed, game)
+            };
+            let trace = args.trace;
+            let mut observer = |event: Event| {
+                if trace {
+                    eprintln!("{}", event);
+                }
+            };
+            if let (Some(pause_at), Some(path)) = (args.pause_at, args.save) {
+                let mut paused = Some(game.clone());
+                while let Some(state) = paused.take() {
+                    if state.moves() >= pause_at {
+                        paused = Some(state);
+                        break;
+                    }
+                    // A game over before the pause is reported like any other.
+                    if let GameStepped::Cont(state) = state.step_with(&rules, &mut observer) {
+                        paused = Some(state);
+                    }
+                }
+                if let Some(paused) = paused {
+                    let bytes = if args.binary { paused.to_bytes() } else { paused.to_text().into_bytes() };
+                    if let Err(error) = fs::write(&path, bytes) {
+                        fail(&format!("cannot write {}: {}", path.display(), error));
+                    }
+                    println!("paused after {} moves", paused.moves());
+                    return;
+                }
+            }
+            let (mut replay, outcome) = Replay::record(game, &rules, args.tricks, &mut observer);
+            if let Some(path) = args.record {
+                replay.generator = cli.rng;
+                replay.seed = seed.clone();
+                let written = File::create(&path).and_then(|file| replay.write(BufWriter::new(file)));
+                if let Err(error) = written {
+                    fail(&format!("cannot write {}: {}", path.display(), error));
+                }
+            }
+            if let Some(header) = args.format.header() {
+                println!("{}", header);
+            }
+            println!("{}", args.format.record(&seed, &outcome, &rules));
+            if rules.players > 2 && args.format == Format::Text {
+                print_standings(&outcome.standings);
+            }
+        }
+        Command::Sweep { seeds, format } => {
+            check_seeds(&seeds);
+            let seeds = seeds.seeds(cli.rng);
+            if let Some(header) = format.header() {
+                println!("{}", header);
+            }
+            sweep(&seeds, &rules, |x, outcome| {
+                println!("{}", format.record(&seeds.seed(x), &outcome, &rules));
+            });
+        }
+        Command::Replay { file } => {
+            let replay = File::open(&file)
+                .map_err(From::from)
+                .and_then(|file| Replay::read(BufReader::new(file)))
+                .unwrap_or_else(|error| fail(&format!("cannot read {}: {}", file.display(), error)));
+            match replay.verify() {
+                Ok(outcome) => println!("{}", outcome.score),
+                Err(error) => fail(&format!("{}: {}", file.display(), error)),
+            }
+        }
+        Command::Tournament { strategies, deals, seed } => {
+            if rules.players != 2 {
+                invalid("tournament needs a two-player game");
+            }
+            if strategies.len() < 2 {
+                invalid("--strategies needs at least two strategies");
+            }
+            let config = Tournament {
+                deals,
+                seed,
+                generator: cli.rng,
+            };
+            print!("{}", tournament(&strategies, &rules, &config));
+        }
+        Command::Stats(args) => {
+            check_seeds(&args);
+            let mut stats = Stats::default();
+            sweep(&args.seeds(cli.rng), &rules, |_, outcome| stats.add(&outcome));
+            println!("{}", stats);
+        }
+        Command::Search(_) if rules.players != 2 => {
+            invalid("search needs a two-player game");
+        }
+        Command::Search(Search::Seeds { seeds, top }) => {
+            check_seeds(&seeds);
+            let seeds = seeds.seeds(cli.rng);
+            let mut board = Leaderboard::new(top, &rules);
+            sweep(&seeds, &rules, |x, outcome| {
+                if let Some(rank) = board.insert(x, outcome.score) {
+                    let seed = seed_words(&seeds.seed(x));
+                    println!("#{} {}: {}", rank + 1, seed, board.entries()[rank].score);
+                }
+            });
+            println!();
+            for (rank, entry) in board.entries().iter().enumerate() {
+                println!("#{} {}: {}", rank + 1, seed_words(&seeds.seed(entry.seed)), entry.score);
+            }
+        }
+        Command::Search(Search::Local {
+            seed,
+            iterations,
+            start_temperature,
+            end_temperature,
+        }) => {
+            if start_temperature < 0.0 || end_temperature < 0.0 {
+                invalid("temperatures must not be negative");
+            }
+            if start_temperature > 0.0 && end_temperature == 0.0 {
+                invalid("--end-temperature must be positive when annealing");
+            }
+            let config = LocalSearch {
+                iterations,
+                start_temperature,
+                end_temperature,
+            };
+            let mut rng = cli.rng.seed(&[seed]);
+            let computer = rules.computer_deck.deal(&rules, &mut *rng);
+            let start = Deck::new_shuffle(&rules, &mut rng);
+            let best = local_search(&computer, start, &rules, &config, &mut rng, |i, best| {
+                println!("{}: {}", i, best.score);
+            });
+            print_best(&best);
+        }
+        Command::Search(Search::Genetic {
+            seed,
+            generations,
+            population,
+            elitism,
+            mutation_rate,
+            tournament_size,
+        }) => {
+            if population == 0 {
+                invalid("--population must be at least 1");
+            }
+            if !(0.0..=1.0).contains(&mutation_rate) {
+                invalid("--mutation-rate must be between 0 and 1");
+            }
+            let config = Genetic {
+                population,
+                elitism,
+                generations,
+                mutation_rate,
+                tournament_size,
+            };
+            let mut rng = cli.rng.seed(&[seed]);
+            let computer = rules.computer_deck.deal(&rules, &mut *rng);
+            let best = evolve(&computer, &rules, &config, &mut rng, |generation| {
+                println!(
+                    "{}: best {}, mean {:.1}",
+                    generation.number, generation.best, generation.mean
+                );
+            });
+            print_best(&best);
+        }
     }
 }
@@ -0,0 +1,120 @@
+use std::fmt;
+
+use {Card, Deck, Score};
+
+/// The name of a seat at the table: `Computer`, `Player`, then `Player 3`
+/// and so on for any extra players.
+pub fn seat_name(seat: usize) -> String {
+    match seat {
+        0 => "Computer".to_string(),
+        1 => "Player".to_string(),
+        seat => format!("Player {}", seat + 1),
+    }
+}
+
+/// Something that happened while playing a trick. Players are identified
+/// by their seat, and per-seat slices are indexed by seat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event<'a> {
+    /// A trick starts after `moves` tricks have been played.
+    TrickStarted { moves: usize },
+    /// The players still contending for the trick turned over a card to
+    /// compare. Seats not contending have `None`.
+    Drawn { cards: &'a [Option<Card>] },
+    /// Two or more players tied for the highest card. `depth` is 1 for a
+    /// new war and counts up as the war escalates. `face_down` is the number
+    /// of cards each of them lays face down, as given by `Rules::face_down`
+    /// for the tied rank.
+    War { depth: usize, face_down: usize },
+    /// The players at war laid a card face down. `None` means that seat is
+    /// not at war or had no card left to lay.
+    FaceDown { cards: &'a [Option<Card>] },
+    /// A player had to play a card but had none left, or could not lay a
+    /// full war under `Exhaustion::LoseImmediately`, and is out of the game.
+    KnockedOut { seat: usize },
+    /// The cards on the table were shuffled and handed back under
+    /// `Exhaustion::Reshuffle`, and the trick starts over.
+    Reshuffled,
+    /// The trick was won. `piles` holds the cards played by each seat; they
+    /// were added to the bottom of the winner's deck in the order given by
+    /// the winner's `PickupPolicy`. `decks` are the decks after collecting.
+    TrickWon {
+        winner: usize,
+        piles: &'a [Deck],
+        decks: &'a [Deck],
+    },
+    /// The game ended.
+    GameOver { score: &'a Score },
+}
+
+impl<'a> fmt::Display for Event<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn cards(cards: &[Option<Card>]) -> String {
+            let cards: Vec<String> = cards
+                .iter()
+                .map(|card| card.map_or_else(|| "-".to_string(), |card| card.to_string()))
+                .collect();
+            cards.join(" vs ")
+        }
+
+        match *self {
+            Event::TrickStarted { moves } => write!(f, "trick {}", moves + 1),
+            Event::Drawn { cards: drawn } => write!(f, "  {}", cards(drawn)),
+            Event::War { depth, face_down } => write!(f, "  war (depth {}, {} face down)", depth, face_down),
+            Event::FaceDown { cards: laid } => write!(f, "  face down {}", cards(laid)),
+            Event::KnockedOut { seat } => write!(f, "  {} is out", seat_name(seat)),
+            Event::Reshuffled => f.write_str("  war reshuffled"),
+            Event::TrickWon {
+                winner,
+                piles,
+                decks,
+            } => {
+                // The collected cards, as they now lie at the bottom of the
+                // winner's deck.
+                let collected: usize = piles.iter().map(Deck::len).sum();
+                let deck = &decks[winner];
+                let cards: Vec<String> = deck
+                    .iter()
+                    .skip(deck.len() - collected)
+                    .map(|card| card.to_string())
+                    .collect();
+                let counts: Vec<String> = decks.iter().map(|deck| deck.len().to_string()).collect();
+                write!(
+                    f,
+                    "  {} collects {} ({} cards)",
+                    seat_name(winner),
+                    cards.join(" "),
+                    counts.join(" vs ")
+                )
+            }
+            Event::GameOver { score } => write!(f, "{}", score),
+        }
+    }
+}
+
+/// Receives the events of a game as it is played.
+///
+/// Closures taking an `Event` are observers, and `NoObserver` ignores
+/// everything. Since the engine is generic over the observer, ignoring
+/// events costs nothing.
+pub trait Observer {
+    fn observe(&mut self, event: Event);
+}
+
+/// An observer that ignores every event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoObserver;
+
+impl Observer for NoObserver {
+    #[inline(always)]
+    fn observe(&mut self, _event: Event) {}
+}
+
+impl<F> Observer for F
+where
+    F: FnMut(Event),
+{
+    fn observe(&mut self, event: Event) {
+        self(event)
+    }
+}
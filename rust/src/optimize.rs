@@ -0,0 +1,209 @@
+use std::cmp::Ordering;
+
+use {play_game, Deck, GameState, Rules, Score, WarRng};
+
+/// A small rearrangement of a deck, used to explore nearby orderings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// Exchange the cards at two positions.
+    Swap(usize, usize),
+    /// Reverse the cards in `start..end`.
+    Reverse(usize, usize),
+    /// Lift the `len` cards starting at `from` and reinsert them so they
+    /// start at `to` in the resulting deck.
+    BlockMove { from: usize, len: usize, to: usize },
+}
+
+impl Mutation {
+    /// Pick a mutation uniformly among the three kinds for a deck of `len`
+    /// cards. `len` must be at least 2.
+    pub fn random<R: WarRng + ?Sized>(len: usize, rng: &mut R) -> Self {
+        assert!(len >= 2, "cannot mutate a deck of {} cards", len);
+        match rng.gen_range(0, 3) {
+            0 => {
+                let i = rng.gen_range(0, len);
+                let j = (i + rng.gen_range(1, len)) % len;
+                Mutation::Swap(i, j)
+            }
+            1 => {
+                let start = rng.gen_range(0, len - 1);
+                let end = rng.gen_range(start + 2, len + 1);
+                Mutation::Reverse(start, end)
+            }
+            _ => {
+                let block = rng.gen_range(1, len);
+                let from = rng.gen_range(0, len - block + 1);
+                let to = rng.gen_range(0, len - block + 1);
+                Mutation::BlockMove { from, len: block, to }
+            }
+        }
+    }
+
+    /// Rearrange `cards` in place.
+    pub fn apply<T>(&self, cards: &mut Vec<T>) {
+        match *self {
+            Mutation::Swap(i, j) => cards.swap(i, j),
+            Mutation::Reverse(start, end) => cards[start..end].reverse(),
+            Mutation::BlockMove { from, len, to } => {
+                let block: Vec<T> = cards.drain(from..from + len).collect();
+                let tail = cards.split_off(to);
+                cards.extend(block);
+                cards.extend(tail);
+            }
+        }
+    }
+}
+
+/// Settings for `local_search`.
+///
+/// The temperature falls geometrically from `start_temperature` to
+/// `end_temperature` over the run. A worse deck is accepted with probability
+/// `exp(-loss / temperature)`, where the loss is measured in `Score::to_int`
+/// units. A start temperature of zero gives plain hill climbing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalSearch {
+    pub iterations: usize,
+    pub start_temperature: f64,
+    pub end_temperature: f64,
+}
+
+impl Default for LocalSearch {
+    fn default() -> Self {
+        LocalSearch {
+            iterations: 1000,
+            start_temperature: 0.0,
+            end_temperature: 0.0,
+        }
+    }
+}
+
+/// A player deck together with its result against the computer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub deck: Deck,
+    pub score: Score,
+    /// `score.to_int()` under the rules it was played with, for searches
+    /// that need to measure how much better one deck is than another.
+    pub value: usize,
+}
+
+impl Candidate {
+    /// Play `deck` as the player against `computer`.
+    pub fn evaluate(computer: &Deck, deck: Deck, rules: &Rules) -> Self {
+        let score = play_game(GameState::from_decks(computer.clone(), deck.clone(), 0), rules);
+        let value = score.to_int(rules);
+        Candidate { deck, score, value }
+    }
+}
+
+/// Improve the player's deck against `computer` by repeatedly mutating it.
+///
+/// Returns the best deck seen. `on_improve` is called with the iteration
+/// number every time a new best is found. Runs are reproducible for a given
+/// `rng` seed.
+pub fn local_search<R, F>(
+    computer: &Deck,
+    start: Deck,
+    rules: &Rules,
+    config: &LocalSearch,
+    rng: &mut R,
+    mut on_improve: F,
+) -> Candidate
+where
+    R: WarRng + ?Sized,
+    F: FnMut(usize, &Candidate),
+{
+    let mut current = Candidate::evaluate(computer, start, rules);
+    let mut best = current.clone();
+    if current.deck.len() < 2 {
+        return best;
+    }
+
+    for iteration in 0..config.iterations {
+        let mut cards = current.deck.to_vec();
+        Mutation::random(cards.len(), rng).apply(&mut cards);
+        let candidate = Candidate::evaluate(computer, Deck::from_vec(cards), rules);
+
+        let accept = if candidate.score.compare(&current.score, rules) != Ordering::Less {
+            true
+        } else {
+            let temperature = temperature(config, iteration);
+            let loss = (current.value - candidate.value) as f64;
+            temperature > 0.0 && rng.next_f64() < (-loss / temperature).exp()
+        };
+        if accept {
+            current = candidate;
+            if current.score.compare(&best.score, rules) == Ordering::Greater {
+                best = current.clone();
+                on_improve(iteration, &best);
+            }
+        }
+    }
+    best
+}
+
+fn temperature(config: &LocalSearch, iteration: usize) -> f64 {
+    if config.start_temperature <= 0.0 {
+        return 0.0;
+    }
+    let progress = iteration as f64 / config.iterations as f64;
+    let ratio = config.end_temperature / config.start_temperature;
+    config.start_temperature * ratio.powf(progress)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Xoshiro256StarStar;
+
+    #[test]
+    fn mutations() {
+        let apply = |mutation: Mutation| {
+            let mut cards = vec![2, 3, 4, 5, 6, 7];
+            mutation.apply(&mut cards);
+            cards
+        };
+
+        assert_eq!(apply(Mutation::Swap(0, 4)), vec![6, 3, 4, 5, 2, 7]);
+        assert_eq!(apply(Mutation::Reverse(1, 4)), vec![2, 5, 4, 3, 6, 7]);
+        assert_eq!(apply(Mutation::BlockMove { from: 1, len: 2, to: 3 }), vec![2, 5, 6, 3, 4, 7]);
+        assert_eq!(apply(Mutation::BlockMove { from: 3, len: 3, to: 0 }), vec![5, 6, 7, 2, 3, 4]);
+    }
+
+    #[test]
+    fn random_mutations_keep_cards() {
+        let mut rng = Xoshiro256StarStar::from_seed(&[1]);
+        let mut cards: Vec<u8> = (2..15).collect();
+        for _ in 0..1000 {
+            Mutation::random(cards.len(), &mut rng).apply(&mut cards);
+            let mut sorted = cards.clone();
+            sorted.sort();
+            assert_eq!(sorted, (2..15).collect::<Vec<u8>>());
+        }
+    }
+
+    #[test]
+    fn reproducible_and_never_worse() {
+        let rules = Rules {
+            copies_per_rank: 2,
+            move_limit: 2000,
+            ..Rules::default()
+        };
+        let computer = Deck::new_half_deck(&rules);
+        let config = LocalSearch {
+            iterations: 200,
+            start_temperature: 1000.0,
+            end_temperature: 1.0,
+        };
+        let run = || {
+            let mut rng = Xoshiro256StarStar::from_seed(&[5]);
+            let start = Deck::new_shuffle(&rules, &mut rng);
+            let initial = Candidate::evaluate(&computer, start.clone(), &rules);
+            let best = local_search(&computer, start, &rules, &config, &mut rng, |_, _| ());
+            assert_ne!(best.score.compare(&initial.score, &rules), Ordering::Less);
+            best
+        };
+
+        assert_eq!(run(), run());
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+use std::str::FromStr;
+
+use {Outcome, Rules, Score};
+
+/// How results are written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `seed: score`, for reading by people.
+    Text,
+    /// One JSON object per line.
+    JsonLines,
+    /// Comma separated values with a header row.
+    Csv,
+}
+
+const CSV_HEADER: &str =
+    "seed,outcome,moves,computer_cards,player_cards,score,wars,period,entered_at,standings";
+
+impl Format {
+    /// The line to write before any records, if the format has one.
+    pub fn header(&self) -> Option<&'static str> {
+        match *self {
+            Format::Csv => Some(CSV_HEADER),
+            Format::Text | Format::JsonLines => None,
+        }
+    }
+
+    /// A single line, without the trailing newline, describing the game
+    /// dealt by `seed`. Games not dealt from a seed pass an empty one.
+    ///
+    /// Multi-word seeds are written as a JSON array, or as space separated
+    /// words in the other formats. The score column is `Score::to_int`
+    /// under `rules`, and `period` and `entered_at` are only filled in for
+    /// cycles. The standings list the seats in finishing order, best first,
+    /// as an array of places in JSON; in CSV places are separated by spaces
+    /// and seats sharing a place by `=`.
+    pub fn record(&self, seed: &[usize], outcome: &Outcome, rules: &Rules) -> String {
+        let words: Vec<String> = seed.iter().map(|word| word.to_string()).collect();
+        let (period, entered_at) = match outcome.score {
+            Score::Cycle { period, entered_at } => (Some(period), Some(entered_at)),
+            _ => (None, None),
+        };
+        match *self {
+            Format::Text if seed.is_empty() => outcome.score.to_string(),
+            Format::Text => format!("{}: {}", words.join(" "), outcome.score),
+            Format::JsonLines => format!(
+                "{{\"seed\":[{}],\"outcome\":\"{}\",\"moves\":{},\"computer_cards\":{},\
+                 \"player_cards\":{},\"score\":{},\"wars\":{},\"period\":{},\"entered_at\":{},\
+                 \"standings\":[{}]}}",
+                words.join(","),
+                outcome.score.kind(),
+                outcome.moves,
+                outcome.computer_cards,
+                outcome.player_cards,
+                outcome.score.to_int(rules),
+                outcome.wars,
+                json_option(period),
+                json_option(entered_at),
+                places(&outcome.standings, "[", ",", "]", ","),
+            ),
+            Format::Csv => format!(
+                "{},{},{},{},{},{},{},{},{},{}",
+                words.join(" "),
+                outcome.score.kind(),
+                outcome.moves,
+                outcome.computer_cards,
+                outcome.player_cards,
+                outcome.score.to_int(rules),
+                outcome.wars,
+                csv_option(period),
+                csv_option(entered_at),
+                places(&outcome.standings, "", "=", "", " "),
+            ),
+        }
+    }
+}
+
+fn json_option(value: Option<usize>) -> String {
+    value.map_or_else(|| "null".to_string(), |value| value.to_string())
+}
+
+/// The standings with each place written as `open`, its seats joined by
+/// `seat`, then `close`, and the places joined by `place`.
+fn places(standings: &[Vec<usize>], open: &str, seat: &str, close: &str, place: &str) -> String {
+    let places: Vec<String> = standings
+        .iter()
+        .map(|seats| {
+            let seats: Vec<String> = seats.iter().map(|seat| seat.to_string()).collect();
+            format!("{}{}{}", open, seats.join(seat), close)
+        })
+        .collect();
+    places.join(place)
+}
+
+fn csv_option(value: Option<usize>) -> String {
+    value.map_or_else(String::new, |value| value.to_string())
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "jsonl" | "json-lines" => Ok(Format::JsonLines),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown output format {:?}, expected text, jsonl or csv", s)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Format::Text => "text",
+            Format::JsonLines => "jsonl",
+            Format::Csv => "csv",
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn outcome(score: Score) -> Outcome {
+        Outcome {
+            score,
+            moves: 84,
+            wars: 5,
+            face_down: 15,
+            computer_cards: 10,
+            player_cards: 16,
+            standings: vec![vec![1], vec![0]],
+        }
+    }
+
+    #[test]
+    fn json_lines() {
+        let rules = Rules::default();
+        let line = Format::JsonLines.record(&[7, 3], &outcome(Score::LoseAfter(84)), &rules);
+        assert_eq!(
+            line,
+            "{\"seed\":[7,3],\"outcome\":\"loss\",\"moves\":84,\"computer_cards\":10,\
+             \"player_cards\":16,\"score\":84,\"wars\":5,\"period\":null,\"entered_at\":null,\
+             \"standings\":[[1],[0]]}"
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let rules = Rules::default();
+        let cycle = Score::Cycle {
+            period: 60,
+            entered_at: 24,
+        };
+        let line = Format::Csv.record(&[12], &outcome(cycle), &rules);
+        assert_eq!(line, "12,cycle,84,10,16,1003328,5,60,24,1 0");
+        assert_eq!(Format::Csv.header().unwrap().split(',').count(), line.split(',').count());
+    }
+
+    #[test]
+    fn standings() {
+        let rules = Rules::default();
+        let outcome = Outcome {
+            standings: vec![vec![2], vec![3], vec![0, 1]],
+            ..outcome(Score::LoseAfter(84))
+        };
+        let json = Format::JsonLines.record(&[1], &outcome, &rules);
+        assert!(json.ends_with(",\"standings\":[[2],[3],[0,1]]}"), "{}", json);
+        let csv = Format::Csv.record(&[1], &outcome, &rules);
+        assert!(csv.ends_with(",2 3 0=1"), "{}", csv);
+    }
+
+    #[test]
+    fn parse() {
+        for format in &[Format::Text, Format::JsonLines, Format::Csv] {
+            assert_eq!(format.to_string().parse::<Format>(), Ok(*format));
+        }
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+
+use {Card, Deck, Rules, Shuffle, Xoshiro256StarStar};
+
+/// The order in which the winner of a trick adds the cards on the table to
+/// the bottom of their deck.
+///
+/// Piles are taken round the table starting with the winner's own, so in a
+/// two-player game `WinnerFirst` adds the winner's pile and then the
+/// loser's. Each seat picks up with its own policy, set in `Rules::pickup`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PickupPolicy {
+    /// The winner's pile, then each other pile going round the table.
+    #[default]
+    WinnerFirst,
+    /// Each other pile going round the table, then the winner's pile.
+    LoserFirst,
+    /// One card from each pile in turn, in `WinnerFirst` order, until every
+    /// pile is used up.
+    Interleaved,
+    /// Every card from the strongest to the weakest, ranked by
+    /// `Rules::strength`. Cards as strong as each other keep their
+    /// `WinnerFirst` order.
+    HighFirst,
+    /// Every card from the weakest to the strongest, ties as for
+    /// `HighFirst`.
+    LowFirst,
+    /// The cards in `WinnerFirst` order, shuffled by a generator seeded
+    /// from the given word, the winning seat and the number of tricks
+    /// played, so a game always plays out the same way.
+    Random(usize),
+}
+
+impl PickupPolicy {
+    /// Whether the policy shuffles. Games using it cannot loop, since the
+    /// shuffle depends on the number of tricks played.
+    pub fn is_random(&self) -> bool {
+        matches!(*self, PickupPolicy::Random(_))
+    }
+
+    /// Add the `piles` played by each seat to the bottom of `deck`, which
+    /// belongs to `winner`, after `moves` tricks of a game played under
+    /// `rules`.
+    pub fn pick_up(&self, rules: &Rules, winner: usize, moves: usize, piles: &[Deck], deck: &mut Deck) {
+        let seats = (winner..piles.len()).chain(0..winner);
+        match *self {
+            PickupPolicy::WinnerFirst => {
+                for seat in seats {
+                    deck.add_all(&piles[seat]);
+                }
+            }
+            PickupPolicy::LoserFirst => {
+                for seat in seats.skip(1) {
+                    deck.add_all(&piles[seat]);
+                }
+                deck.add_all(&piles[winner]);
+            }
+            PickupPolicy::Interleaved => {
+                let mut cards: Vec<_> = seats.map(|seat| piles[seat].iter()).collect();
+                let mut added = true;
+                while added {
+                    added = false;
+                    for card in cards.iter_mut().filter_map(|pile| pile.next()) {
+                        deck.add(*card);
+                        added = true;
+                    }
+                }
+            }
+            PickupPolicy::HighFirst | PickupPolicy::LowFirst => {
+                let mut cards: Vec<Card> = seats.flat_map(|seat| piles[seat].iter().cloned()).collect();
+                let high = *self == PickupPolicy::HighFirst;
+                match (rules.compares_by_rank(), high) {
+                    (true, true) => cards.sort_by(|a, b| b.cmp_rank(*a)),
+                    (true, false) => cards.sort_by(|a, b| a.cmp_rank(*b)),
+                    (false, true) => cards.sort_by_cached_key(|&card| Reverse(rules.strength(card))),
+                    (false, false) => cards.sort_by_cached_key(|&card| rules.strength(card)),
+                }
+                deck.add_pile(Deck::from_vec(cards));
+            }
+            PickupPolicy::Random(seed) => {
+                let mut cards = Deck::new_empty();
+                for seat in seats {
+                    cards.add_all(&piles[seat]);
+                }
+                let mut rng = Xoshiro256StarStar::from_seed(&[seed, winner, moves]);
+                cards.shuffle(Shuffle::V1, &mut rng);
+                deck.add_pile(cards);
+            }
+        }
+    }
+}
+
+impl FromStr for PickupPolicy {
+    type Err = String;
+
+    /// Parse `winner-first`, `loser-first`, `interleaved`, `high-first`,
+    /// `low-first` or `random:SEED`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "winner-first" => Ok(PickupPolicy::WinnerFirst),
+            "loser-first" => Ok(PickupPolicy::LoserFirst),
+            "interleaved" => Ok(PickupPolicy::Interleaved),
+            "high-first" => Ok(PickupPolicy::HighFirst),
+            "low-first" => Ok(PickupPolicy::LowFirst),
+            _ => match s.strip_prefix("random:").map(str::parse) {
+                Some(Ok(seed)) => Ok(PickupPolicy::Random(seed)),
+                _ => Err(format!(
+                    "unknown pickup policy {:?}, expected winner-first, loser-first, \
+                     interleaved, high-first, low-first or random:SEED",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for PickupPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PickupPolicy::WinnerFirst => f.write_str("winner-first"),
+            PickupPolicy::LoserFirst => f.write_str("loser-first"),
+            PickupPolicy::Interleaved => f.write_str("interleaved"),
+            PickupPolicy::HighFirst => f.write_str("high-first"),
+            PickupPolicy::LowFirst => f.write_str("low-first"),
+            PickupPolicy::Random(seed) => write!(f, "random:{}", seed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Comparison;
+
+    fn deck(cards: &str) -> Deck {
+        cards.parse().unwrap()
+    }
+
+    fn pick_up(policy: PickupPolicy, winner: usize, piles: &[&str]) -> Deck {
+        pick_up_under(&Rules::default(), policy, winner, piles)
+    }
+
+    fn pick_up_under(rules: &Rules, policy: PickupPolicy, winner: usize, piles: &[&str]) -> Deck {
+        let piles: Vec<Deck> = piles.iter().map(|pile| deck(pile)).collect();
+        let mut won = deck("K");
+        policy.pick_up(rules, winner, 0, &piles, &mut won);
+        won
+    }
+
+    #[test]
+    fn orders() {
+        let piles = ["2 8 9 T J", "2 3 4 5 6", "A"];
+        for &(policy, order) in &[
+            (PickupPolicy::WinnerFirst, "K A 2 8 9 T J 2 3 4 5 6"),
+            (PickupPolicy::LoserFirst, "K 2 8 9 T J 2 3 4 5 6 A"),
+            (PickupPolicy::Interleaved, "K A 2 2 8 3 9 4 T 5 J 6"),
+            (PickupPolicy::HighFirst, "K A J T 9 8 6 5 4 3 2 2"),
+            (PickupPolicy::LowFirst, "K 2 2 3 4 5 6 8 9 T J A"),
+        ] {
+            assert_eq!(pick_up(policy, 2, &piles), deck(order), "{}", policy);
+        }
+        assert_eq!(pick_up(PickupPolicy::LoserFirst, 0, &["4", "2"]), deck("K 2 4"));
+    }
+
+    #[test]
+    fn follows_the_comparison() {
+        let peace = Rules {
+            comparison: Comparison::Peace,
+            ..Rules::default()
+        };
+        let piles = ["2 8 9 T J", "2D 3 4 5 6", "A"];
+        let high = pick_up_under(&peace, PickupPolicy::HighFirst, 2, &piles);
+        assert_eq!(high, deck("K 2 2D 3 4 5 6 8 9 T J A"));
+        let low = pick_up_under(&peace, PickupPolicy::LowFirst, 2, &piles);
+        assert_eq!(low, deck("K A J T 9 8 6 5 4 3 2 2D"));
+    }
+
+    #[test]
+    fn random_is_seeded() {
+        let piles = ["2 8 9 T J", "2 3 4 5 6"];
+        let once = pick_up(PickupPolicy::Random(3), 1, &piles);
+        assert_eq!(pick_up(PickupPolicy::Random(3), 1, &piles), once);
+        assert_ne!(pick_up(PickupPolicy::Random(4), 1, &piles), once);
+
+        let mut cards = once.to_vec();
+        cards.sort_by_key(|card| card.to_byte());
+        assert_eq!(Deck::from_vec(cards), deck("2 2 3 4 5 6 8 9 T J K"));
+    }
+
+    #[test]
+    fn notation() {
+        for &policy in &[
+            PickupPolicy::WinnerFirst,
+            PickupPolicy::LoserFirst,
+            PickupPolicy::Interleaved,
+            PickupPolicy::HighFirst,
+            PickupPolicy::LowFirst,
+            PickupPolicy::Random(17),
+        ] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        assert!("random".parse::<PickupPolicy>().is_err());
+        assert!("random:x".parse::<PickupPolicy>().is_err());
+    }
+}
@@ -0,0 +1,650 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use {
+    play_game_outcome, play_game_with, Deck, Event, GameState, GameStepped, Generator, Observer,
+    seat_name, Outcome, Rules, Score,
+};
+
+/// The first line of every replay file, followed by the format version.
+const MAGIC: &str = "war-replay";
+
+/// The version written by `Replay::write`. Files with any other version are
+/// rejected rather than misread.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// A single trick of a recorded game: the seat that won it and how many
+/// cards they collected, their own included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trick {
+    pub winner: usize,
+    pub cards: usize,
+}
+
+impl fmt::Display for Trick {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} collects {} cards", seat_name(self.winner), self.cards)
+    }
+}
+
+/// A recorded game: the rules, the starting decks and how the game ended,
+/// plus optionally every trick played along the way.
+///
+/// Replays are saved as a line-based text file:
+///
+/// ```text
+/// war-replay 1
+/// move-limit 1000000
+/// copies 256
+/// ranks 2 3 4 5 6 7 8 9 T J Q K A
+/// jokers 0
+/// wild-ranks
+/// wild beats
+/// comparison standard
+/// suits-break-ties false
+/// face-down 3
+/// war-depth fixed
+/// players 2
+/// computer-deck sorted
+/// player-deck dealt
+/// pickup winner-first
+/// exhaustion skip
+/// seed rand03 1
+/// moves 0
+/// computer [2C 3C 4C ... AS]*64
+/// player 9H KD 4C ...
+/// score win 226918
+/// tricks 226918
+/// 1 2
+/// 0 10
+/// ...
+/// ```
+///
+/// The rule lines each give the matching `Rules` field, keyed by name. They
+/// may come in any order, and any that are missing take their value from
+/// `Rules::default()`, so a rule added later does not change the format. The
+/// `pickup` line lists the pickup policy of each seat, computer first, and
+/// is empty when every seat uses the default, as `wild-ranks` is when no
+/// rank is wild. Decks are in card notation, one line per seat, with any
+/// extra seats after the player's as `player-3` and so on. The `seed` line
+/// is only present for games dealt from a seed, an `out` line listing seat
+/// and move pairs only for games resumed after a seat was knocked out, and
+/// the `tricks` section only when tricks were recorded. Each trick line
+/// holds the winning seat and the number of cards collected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub rules: Rules,
+    /// The generator the player deck was dealt with. Only meaningful when
+    /// `seed` is not empty.
+    pub generator: Generator,
+    /// The seed the player deck was dealt from, or empty for games between
+    /// explicit decks.
+    pub seed: Vec<usize>,
+    /// Tricks already played when the recording starts.
+    pub moves: usize,
+    /// The deck of each seat when the recording starts.
+    pub decks: Vec<Deck>,
+    /// Seats already knocked out when the recording starts, as returned by
+    /// `GameState::knocked_out`.
+    pub out: Vec<(usize, usize)>,
+    pub score: Score,
+    pub tricks: Option<Vec<Trick>>,
+}
+
+/// Why a replay could not be loaded or no longer matches the engine.
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// The file is malformed. Lines count from 1.
+    Parse { line: usize, message: String },
+    /// The seed no longer deals the recorded decks.
+    Deal,
+    /// The engine played trick number `trick`, counting from 1, differently.
+    /// `None` means the recording or the engine had no such trick.
+    Diverged {
+        trick: usize,
+        recorded: Option<Trick>,
+        played: Option<Trick>,
+    },
+    /// The game ended with a different score.
+    Score { recorded: Score, played: Score },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn trick(trick: &Option<Trick>) -> String {
+            trick.map_or_else(|| "nothing".to_string(), |trick| trick.to_string())
+        }
+
+        match *self {
+            ReplayError::Io(ref error) => write!(f, "{}", error),
+            ReplayError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+            ReplayError::Deal => f.write_str("the seed no longer deals the recorded decks"),
+            ReplayError::Diverged {
+                trick: number,
+                ref recorded,
+                ref played,
+            } => write!(
+                f,
+                "diverged at trick {}: recorded {}, played {}",
+                number,
+                trick(recorded),
+                trick(played)
+            ),
+            ReplayError::Score {
+                ref recorded,
+                ref played,
+            } => write!(f, "recorded {}, played {}", recorded, played),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+impl Replay {
+    /// Play `game` to the end, recording it. Every event is also passed on
+    /// to `observer`.
+    ///
+    /// The replay has no seed; set `generator` and `seed` afterwards for
+    /// games dealt from one.
+    pub fn record<O: Observer>(
+        game: GameState,
+        rules: &Rules,
+        keep_tricks: bool,
+        observer: &mut O,
+    ) -> (Replay, Outcome) {
+        let mut replay = Replay {
+            rules: rules.clone(),
+            generator: Generator::default(),
+            seed: Vec::new(),
+            moves: game.moves(),
+            decks: game.decks().to_vec(),
+            out: game.knocked_out().to_vec(),
+            score: Score::TiedAt(0),
+            tricks: None,
+        };
+        let mut tricks = Vec::new();
+        let outcome = play_game_with(game, rules, &mut |event: Event| {
+            if keep_tricks {
+                tricks.extend(trick(&event));
+            }
+            observer.observe(event);
+        });
+        replay.score = outcome.score.clone();
+        if keep_tricks {
+            replay.tricks = Some(tricks);
+        }
+        (replay, outcome)
+    }
+
+    /// The position the recording starts from.
+    pub fn game(&self) -> GameState {
+        GameState::from_seats(self.decks.clone(), self.out.clone(), self.moves, 0)
+    }
+
+    /// Play the game again and check that it goes exactly as recorded.
+    ///
+    /// The seed, if any, must deal the recorded decks; each recorded
+    /// trick must match the one played by `GameState::step`; and the game
+    /// must end with the recorded score. The first difference is returned
+    /// as an error.
+    pub fn verify(&self) -> Result<Outcome, ReplayError> {
+        if !self.seed.is_empty() {
+            let dealt = GameState::new(&self.rules, &mut self.generator.seed(&self.seed));
+            if dealt.decks() != &self.decks[..] {
+                return Err(ReplayError::Deal);
+            }
+        }
+
+        let outcome = play_game_outcome(self.game(), &self.rules);
+        if let Some(ref tricks) = self.tricks {
+            let mut game = self.game();
+            for (i, &recorded) in tricks.iter().enumerate() {
+                let mut played = None;
+                let stepped = game.step_with(&self.rules, &mut |event: Event| {
+                    played = played.or_else(|| trick(&event));
+                });
+                if played != Some(recorded) {
+                    return Err(ReplayError::Diverged {
+                        trick: i + 1,
+                        recorded: Some(recorded),
+                        played,
+                    });
+                }
+                game = match stepped {
+                    GameStepped::Cont(game) => game,
+                    GameStepped::Done(_) => unreachable!("a trick was won"),
+                };
+            }
+            // Finished games end by running out of cards or moves, and
+            // cycles once the loop has been seen, so the engine must play no
+            // more tricks than were recorded.
+            let played = outcome.moves - self.moves;
+            if played > tricks.len() {
+                let mut extra = None;
+                let _ = game.step_with(&self.rules, &mut |event: Event| {
+                    extra = extra.or_else(|| trick(&event));
+                });
+                return Err(ReplayError::Diverged {
+                    trick: tricks.len() + 1,
+                    recorded: None,
+                    played: extra,
+                });
+            }
+        }
+
+        if outcome.score != self.score {
+            return Err(ReplayError::Score {
+                recorded: self.score.clone(),
+                played: outcome.score,
+            });
+        }
+        Ok(outcome)
+    }
+
+    /// Write the replay in the format described above.
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let rules = &self.rules;
+        writeln!(out, "{} {}", MAGIC, REPLAY_VERSION)?;
+        writeln!(out, "move-limit {}", rules.move_limit)?;
+        writeln!(out, "copies {}", rules.copies_per_rank)?;
+        writeln!(out, "ranks {}", words(rules.ranks.iter()))?;
+        writeln!(out, "jokers {}", rules.jokers)?;
+        writeln!(out, "wild-ranks {}", words(rules.wild_ranks.iter()))?;
+        writeln!(out, "wild {}", rules.wild)?;
+        writeln!(out, "comparison {}", rules.comparison)?;
+        writeln!(out, "suits-break-ties {}", rules.suits_break_ties)?;
+        writeln!(out, "face-down {}", rules.face_down_per_war)?;
+        writeln!(out, "war-depth {}", rules.war_depth)?;
+        writeln!(out, "players {}", rules.players)?;
+        writeln!(out, "computer-deck {}", rules.computer_deck)?;
+        writeln!(out, "player-deck {}", rules.player_deck)?;
+        writeln!(out, "pickup {}", words(rules.pickup.iter()))?;
+        writeln!(out, "exhaustion {}", rules.exhaustion)?;
+        if !self.seed.is_empty() {
+            writeln!(out, "seed {} {}", self.generator, words(self.seed.iter()))?;
+        }
+        writeln!(out, "moves {}", self.moves)?;
+        if !self.out.is_empty() {
+            let pairs = self.out.iter().flat_map(|&(seat, moves)| vec![seat, moves]);
+            writeln!(out, "out {}", words(pairs))?;
+        }
+        for (seat, deck) in self.decks.iter().enumerate() {
+            writeln!(out, "{} {}", deck_key(seat), deck)?;
+        }
+        writeln!(out, "score {}", score_words(&self.score))?;
+        if let Some(ref tricks) = self.tricks {
+            writeln!(out, "tricks {}", tricks.len())?;
+            for trick in tricks {
+                writeln!(out, "{} {}", trick.winner, trick.cards)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a replay written by `write`.
+    pub fn read<R: BufRead>(input: R) -> Result<Replay, ReplayError> {
+        let mut lines = Lines {
+            lines: input.lines(),
+            number: 0,
+        };
+
+        let version: u32 = lines.field(MAGIC)?;
+        if version != REPLAY_VERSION {
+            return Err(lines.error(format!(
+                "unsupported replay version {}, expected {}",
+                version, REPLAY_VERSION
+            )));
+        }
+        let mut rules = Rules::default();
+        let (mut key, mut value) = lines.next()?;
+        while key != "seed" && key != "moves" {
+            match &key[..] {
+                "move-limit" => rules.move_limit = lines.parse(&key, &value)?,
+                "copies" => rules.copies_per_rank = lines.parse(&key, &value)?,
+                "ranks" => rules.ranks = parse_words(&value).map_err(|e| lines.error(e))?,
+                "jokers" => rules.jokers = lines.parse(&key, &value)?,
+                "wild-ranks" => rules.wild_ranks = parse_words(&value).map_err(|e| lines.error(e))?,
+                "wild" => rules.wild = lines.parse(&key, &value)?,
+                "comparison" => rules.comparison = lines.parse(&key, &value)?,
+                "suits-break-ties" => rules.suits_break_ties = lines.parse(&key, &value)?,
+                "face-down" => rules.face_down_per_war = lines.parse(&key, &value)?,
+                "war-depth" => rules.war_depth = lines.parse(&key, &value)?,
+                "players" => rules.players = lines.parse(&key, &value)?,
+                "computer-deck" => rules.computer_deck = lines.parse(&key, &value)?,
+                "player-deck" => rules.player_deck = lines.parse(&key, &value)?,
+                "pickup" => rules.pickup = parse_words(&value).map_err(|e| lines.error(e))?,
+                "exhaustion" => rules.exhaustion = lines.parse(&key, &value)?,
+                _ => return Err(lines.error(format!("unknown rule {:?}", key))),
+            }
+            let (next_key, next_value) = lines.next()?;
+            key = next_key;
+            value = next_value;
+        }
+        if rules.players < 2 {
+            return Err(lines.error("a game needs at least two players".to_string()));
+        }
+
+        let (generator, seed, moves) = if key == "seed" {
+            let mut words = value.splitn(2, ' ');
+            let generator = words.next().unwrap_or("").parse().map_err(|e| lines.error(e))?;
+            let seed = parse_words(words.next().unwrap_or("")).map_err(|e| lines.error(e))?;
+            (generator, seed, lines.field("moves")?)
+        } else {
+            let moves = lines.value("moves", &key, &value)?;
+            (Generator::default(), Vec::new(), moves)
+        };
+
+        let mut decks = Vec::with_capacity(rules.players);
+        let mut out = Vec::new();
+        for seat in 0..rules.players {
+            let (key, value) = lines.next()?;
+            if seat == 0 && key == "out" {
+                let pairs: Vec<usize> = parse_words(&value).map_err(|e| lines.error(e))?;
+                if !pairs.len().is_multiple_of(2) {
+                    return Err(lines.error("expected seat and move pairs".to_string()));
+                }
+                out = pairs.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+                decks.push(lines.deck(&deck_key(seat))?);
+                continue;
+            }
+            lines.expect(&deck_key(seat), &key)?;
+            decks.push(value.parse().map_err(|e| lines.error(e))?);
+        }
+        for (i, &(seat, _)) in out.iter().enumerate() {
+            if seat >= rules.players || out[..i].iter().any(|&(other, _)| other == seat) {
+                return Err(lines.error(format!("invalid knocked out seat {}", seat)));
+            }
+        }
+        let score = {
+            let (key, value) = lines.next()?;
+            lines.expect("score", &key)?;
+            parse_score(&value).map_err(|e| lines.error(e))?
+        };
+
+        let tricks = match lines.lines.next() {
+            None => None,
+            Some(line) => {
+                lines.number += 1;
+                let line = line?;
+                let count: usize = {
+                    let (key, value) = split(&line);
+                    lines.value("tricks", key, value)?
+                };
+                let mut tricks = Vec::with_capacity(count);
+                for _ in 0..count {
+                    let (key, value) = lines.next()?;
+                    let winner = match key.parse() {
+                        Ok(seat) if seat < rules.players => seat,
+                        _ => return Err(lines.error(format!("expected a seat, found {:?}", key))),
+                    };
+                    let cards = value.parse().map_err(|_| {
+                        lines.error(format!("expected a number of cards, found {:?}", value))
+                    })?;
+                    tricks.push(Trick { winner, cards });
+                }
+                Some(tricks)
+            }
+        };
+        if lines.lines.next().is_some() {
+            lines.number += 1;
+            return Err(lines.error("unexpected line after the last trick".to_string()));
+        }
+
+        Ok(Replay {
+            rules,
+            generator,
+            seed,
+            moves,
+            decks,
+            out,
+            score,
+            tricks,
+        })
+    }
+}
+
+/// The trick won in `event`, if it reports one.
+fn trick(event: &Event) -> Option<Trick> {
+    match *event {
+        Event::TrickWon { winner, piles, .. } => Some(Trick {
+            winner,
+            cards: piles.iter().map(Deck::len).sum(),
+        }),
+        _ => None,
+    }
+}
+
+/// The key of the line holding the deck of `seat`.
+pub fn deck_key(seat: usize) -> String {
+    match seat {
+        0 => "computer".to_string(),
+        1 => "player".to_string(),
+        seat => format!("player-{}", seat + 1),
+    }
+}
+
+fn words<T: ToString, I: Iterator<Item = T>>(values: I) -> String {
+    let words: Vec<String> = values.map(|value| value.to_string()).collect();
+    words.join(" ")
+}
+
+fn parse_words<T: ::std::str::FromStr>(s: &str) -> Result<Vec<T>, String> {
+    s.split_whitespace()
+        .map(|word| word.parse().map_err(|_| format!("invalid value {:?}", word)))
+        .collect()
+}
+
+fn score_words(score: &Score) -> String {
+    match *score {
+        Score::WinAfter(n) | Score::LoseAfter(n) | Score::FinishWith(n) | Score::TiedAt(n) => {
+            format!("{} {}", score.kind(), n)
+        }
+        Score::Cycle { period, entered_at } => {
+            format!("{} {} {}", score.kind(), period, entered_at)
+        }
+    }
+}
+
+fn parse_score(s: &str) -> Result<Score, String> {
+    let mut words = s.splitn(2, ' ');
+    let kind = words.next().unwrap_or("");
+    let numbers: Vec<usize> = parse_words(words.next().unwrap_or(""))?;
+    match (kind, &numbers[..]) {
+        ("win", &[n]) => Ok(Score::WinAfter(n)),
+        ("loss", &[n]) => Ok(Score::LoseAfter(n)),
+        ("finish", &[n]) => Ok(Score::FinishWith(n)),
+        ("tie", &[n]) => Ok(Score::TiedAt(n)),
+        ("cycle", &[period, entered_at]) => Ok(Score::Cycle { period, entered_at }),
+        _ => Err(format!("invalid score {:?}", s)),
+    }
+}
+
+/// Split a line into its key and the rest.
+fn split(line: &str) -> (&str, &str) {
+    let mut parts = line.splitn(2, ' ');
+    (parts.next().unwrap_or(""), parts.next().unwrap_or("").trim())
+}
+
+/// The lines of a replay file, counted for error messages.
+struct Lines<B> {
+    lines: io::Lines<B>,
+    number: usize,
+}
+
+impl<B: BufRead> Lines<B> {
+    fn error(&self, message: String) -> ReplayError {
+        ReplayError::Parse {
+            line: self.number,
+            message,
+        }
+    }
+
+    fn next(&mut self) -> Result<(String, String), ReplayError> {
+        self.number += 1;
+        match self.lines.next() {
+            None => Err(self.error("unexpected end of file".to_string())),
+            Some(line) => {
+                let line = line?;
+                let (key, value) = split(&line);
+                Ok((key.to_string(), value.to_string()))
+            }
+        }
+    }
+
+    fn expect(&self, expected: &str, key: &str) -> Result<(), ReplayError> {
+        if key == expected {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {:?}, found {:?}", expected, key)))
+        }
+    }
+
+    fn value<T: ::std::str::FromStr>(
+        &self,
+        expected: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<T, ReplayError> {
+        self.expect(expected, key)?;
+        self.parse(expected, value)
+    }
+
+    /// Parse the value of a line starting with `key`.
+    fn parse<T: ::std::str::FromStr>(&self, key: &str, value: &str) -> Result<T, ReplayError> {
+        value
+            .parse()
+            .map_err(|_| self.error(format!("invalid {} {:?}", key, value)))
+    }
+
+    /// The value of the next line, which must start with `key`.
+    fn field<T: ::std::str::FromStr>(&mut self, key: &str) -> Result<T, ReplayError> {
+        let (found, value) = self.next()?;
+        self.value(key, &found, &value)
+    }
+
+    /// The deck on the next line, which must start with `key`.
+    fn deck(&mut self, key: &str) -> Result<Deck, ReplayError> {
+        let (found, value) = self.next()?;
+        self.expect(key, &found)?;
+        value.parse().map_err(|e| self.error(e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use NoObserver;
+
+    fn record(keep_tricks: bool) -> Replay {
+        let rules = Rules {
+            copies_per_rank: 2,
+            ..Rules::default()
+        };
+        let seed = vec![3, 1];
+        let game = GameState::new(&rules, &mut Generator::Xoshiro256.seed(&seed));
+        let (mut replay, _) = Replay::record(game, &rules, keep_tricks, &mut NoObserver);
+        replay.generator = Generator::Xoshiro256;
+        replay.seed = seed;
+        replay
+    }
+
+    fn round_trip(replay: &Replay) -> Replay {
+        let mut file = Vec::new();
+        replay.write(&mut file).unwrap();
+        Replay::read(&file[..]).unwrap()
+    }
+
+    #[test]
+    fn round_trip_and_verify() {
+        for &keep_tricks in &[false, true] {
+            let replay = record(keep_tricks);
+            assert_eq!(round_trip(&replay), replay);
+            let outcome = replay.verify().unwrap();
+            assert_eq!(outcome.score, replay.score);
+            if let Some(ref tricks) = replay.tricks {
+                assert_eq!(tricks.len(), outcome.moves);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_with_more_players() {
+        let rules = Rules {
+            copies_per_rank: 1,
+            players: 3,
+            ..Rules::default()
+        };
+        let game = GameState::new(&rules, &mut Generator::Xoshiro256.seed(&[5]));
+        let (replay, outcome) = Replay::record(game, &rules, true, &mut NoObserver);
+        assert_eq!(replay.decks.len(), 3);
+        assert_eq!(round_trip(&replay), replay);
+        assert_eq!(replay.verify().unwrap(), outcome);
+    }
+
+    #[test]
+    fn detects_divergence() {
+        let mut replay = record(true);
+        let trick = {
+            let tricks = replay.tricks.as_mut().unwrap();
+            tricks[4].cards += 1;
+            tricks[4]
+        };
+        match replay.verify() {
+            Err(ReplayError::Diverged {
+                trick: 5,
+                recorded: Some(recorded),
+                played: Some(_),
+            }) => assert_eq!(recorded, trick),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let mut replay = record(true);
+        replay.tricks.as_mut().unwrap().pop();
+        match replay.verify() {
+            Err(ReplayError::Diverged { recorded: None, .. }) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let mut replay = record(false);
+        replay.seed = vec![3, 2];
+        assert!(matches!(replay.verify(), Err(ReplayError::Deal)));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut file = Vec::new();
+        record(false).write(&mut file).unwrap();
+        let file = String::from_utf8(file).unwrap().replacen("war-replay 1", "war-replay 2", 1);
+        match Replay::read(file.as_bytes()) {
+            Err(ReplayError::Parse { line: 1, .. }) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_rules_take_their_defaults() {
+        let mut file = Vec::new();
+        record(false).write(&mut file).unwrap();
+        let file = String::from_utf8(file).unwrap();
+        let kept: Vec<&str> = file
+            .lines()
+            .filter(|line| !line.starts_with("wild") && !line.starts_with("exhaustion"))
+            .collect();
+        let replay = Replay::read(kept.join("\n").as_bytes()).unwrap();
+        assert_eq!(replay, record(false));
+
+        let file = file.replacen("jokers 0", "joker 0", 1);
+        match Replay::read(file.as_bytes()) {
+            Err(ReplayError::Parse { line: 5, .. }) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt;
+use std::num::Wrapping as w;
+use std::str::FromStr;
+
+/// A source of random numbers for dealing decks and driving searches.
+///
+/// Only `next_u64` must be provided. The other methods derive from it with
+/// the same algorithms rand 0.3 uses on 64-bit platforms, so a generator
+/// producing the same words as a rand 0.3 generator deals the same decks.
+pub trait WarRng {
+    fn next_u64(&mut self) -> u64;
+
+    /// A number drawn uniformly from `low..high`.
+    ///
+    /// Words are drawn until one falls below the largest multiple of the
+    /// range that fits in a `u64`, and the remainder is taken.
+    fn gen_range(&mut self, low: usize, high: usize) -> usize {
+        assert!(low < high, "gen_range called with low >= high");
+        let range = (high - low) as u64;
+        let zone = u64::MAX - u64::MAX % range;
+        loop {
+            let v = self.next_u64();
+            if v < zone {
+                return low + (v % range) as usize;
+            }
+        }
+    }
+
+    /// A number drawn uniformly from `[0, 1)`, built from the low 52 bits
+    /// of one word.
+    fn next_f64(&mut self) -> f64 {
+        const UPPER_MASK: u64 = 0x3FF0000000000000;
+        const LOWER_MASK: u64 = 0xFFFFFFFFFFFFF;
+        f64::from_bits(UPPER_MASK | (self.next_u64() & LOWER_MASK)) - 1.0
+    }
+}
+
+impl<R: WarRng + ?Sized> WarRng for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+impl<R: WarRng + ?Sized> WarRng for Box<R> {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// The generators a seed can be expanded with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Generator {
+    /// `Isaac64`, reproducing rand 0.3's `StdRng`. All results recorded
+    /// before the engine shipped its own generators used this.
+    #[default]
+    Rand03,
+    /// `Xoshiro256StarStar`, which is much cheaper to seed.
+    Xoshiro256,
+}
+
+impl Generator {
+    /// A generator of this kind seeded with the given words.
+    pub fn seed(self, seed: &[usize]) -> Box<dyn WarRng + Send> {
+        match self {
+            Generator::Rand03 => Box::new(Isaac64::from_seed(seed)),
+            Generator::Xoshiro256 => Box::new(Xoshiro256StarStar::from_seed(seed)),
+        }
+    }
+}
+
+impl FromStr for Generator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "rand03" => Ok(Generator::Rand03),
+            "xoshiro256" => Ok(Generator::Xoshiro256),
+            _ => Err(format!("unknown generator {:?}, expected rand03 or xoshiro256", s)),
+        }
+    }
+}
+
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Generator::Rand03 => "rand03",
+            Generator::Xoshiro256 => "xoshiro256",
+        })
+    }
+}
+
+const ISAAC_LOG: usize = 8;
+const ISAAC_SIZE: usize = 1 << ISAAC_LOG;
+
+/// Bob Jenkins' ISAAC-64, seeded exactly like rand 0.3's
+/// `StdRng::from_seed(&[usize])` on 64-bit platforms.
+///
+/// This is a compatibility generator: its output is frozen so that every
+/// seed keeps dealing the deck it dealt under rand 0.3.
+#[derive(Clone)]
+pub struct Isaac64 {
+    cnt: usize,
+    rsl: [w<u64>; ISAAC_SIZE],
+    mem: [w<u64>; ISAAC_SIZE],
+    a: w<u64>,
+    b: w<u64>,
+    c: w<u64>,
+}
+
+impl Isaac64 {
+    /// Seed the generator. At most 256 words are used; the rest of the
+    /// seed state is filled with zeros.
+    pub fn from_seed(seed: &[usize]) -> Self {
+        let mut rng = Isaac64 {
+            cnt: 0,
+            rsl: [w(0); ISAAC_SIZE],
+            mem: [w(0); ISAAC_SIZE],
+            a: w(0),
+            b: w(0),
+            c: w(0),
+        };
+        for (rsl, &word) in rng.rsl.iter_mut().zip(seed) {
+            *rsl = w(word as u64);
+        }
+        rng.init();
+        rng
+    }
+
+    fn init(&mut self) {
+        let mut v = [w(0x9e3779b97f4a7c13u64); 8];
+        #[rustfmt::skip]
+        fn mix(v: &mut [w<u64>; 8]) {
+            v[0] -= v[4]; v[5] ^= v[7] >> 9; v[7] += v[0];
+            v[1] -= v[5]; v[6] ^= v[0] << 9; v[0] += v[1];
+            v[2] -= v[6]; v[7] ^= v[1] >> 23; v[1] += v[2];
+            v[3] -= v[7]; v[0] ^= v[2] << 15; v[2] += v[3];
+            v[4] -= v[0]; v[1] ^= v[3] >> 14; v[3] += v[4];
+            v[5] -= v[1]; v[2] ^= v[4] << 20; v[4] += v[5];
+            v[6] -= v[2]; v[3] ^= v[5] >> 17; v[5] += v[6];
+            v[7] -= v[3]; v[4] ^= v[6] << 14; v[6] += v[7];
+        }
+
+        for _ in 0..4 {
+            mix(&mut v);
+        }
+        for pass in 0..2 {
+            for i in (0..ISAAC_SIZE).step_by(8) {
+                let seed = if pass == 0 { &self.rsl } else { &self.mem };
+                for (x, &word) in v.iter_mut().zip(&seed[i..i + 8]) {
+                    *x += word;
+                }
+                mix(&mut v);
+                self.mem[i..i + 8].copy_from_slice(&v);
+            }
+        }
+        self.refill();
+    }
+
+    fn refill(&mut self) {
+        const HALF: usize = ISAAC_SIZE / 2;
+        let ind = |mem: &[w<u64>; ISAAC_SIZE], x: w<u64>| mem[(x.0 >> 3) as usize & (ISAAC_SIZE - 1)];
+
+        self.c += w(1);
+        let mut a = self.a;
+        let mut b = self.b + self.c;
+        for &(offset, other) in &[(0, HALF), (HALF, 0)] {
+            for i in 0..HALF {
+                let mix = match i % 4 {
+                    0 => !(a ^ (a << 21)),
+                    1 => a ^ (a >> 5),
+                    2 => a ^ (a << 12),
+                    _ => a ^ (a >> 33),
+                };
+                let x = self.mem[offset + i];
+                a = mix + self.mem[other + i];
+                let y = ind(&self.mem, x) + a + b;
+                self.mem[offset + i] = y;
+                b = ind(&self.mem, y >> ISAAC_LOG) + x;
+                self.rsl[offset + i] = b;
+            }
+        }
+        self.a = a;
+        self.b = b;
+        self.cnt = ISAAC_SIZE;
+    }
+}
+
+impl WarRng for Isaac64 {
+    fn next_u64(&mut self) -> u64 {
+        if self.cnt == 0 {
+            self.refill();
+        }
+        self.cnt -= 1;
+        self.rsl[self.cnt].0
+    }
+}
+
+/// Vigna and Blackman's xoshiro256**.
+///
+/// The seed words are absorbed into a SplitMix64 state, starting from the
+/// number of words: each word is xored into the state, which is then
+/// replaced by the next SplitMix64 output. The next four SplitMix64 outputs
+/// form the xoshiro state. This seeding is frozen, so a seed always
+/// produces the same stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xoshiro256StarStar {
+    s: [u64; 4],
+}
+
+impl Xoshiro256StarStar {
+    /// A generator seeded with the given words, as described above.
+    pub fn from_seed(seed: &[usize]) -> Self {
+        let mut state = seed.len() as u64;
+        for &word in seed {
+            state ^= word as u64;
+            state = splitmix64(&mut state);
+        }
+        Xoshiro256StarStar {
+            s: [
+                splitmix64(&mut state),
+                splitmix64(&mut state),
+                splitmix64(&mut state),
+                splitmix64(&mut state),
+            ],
+        }
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+impl WarRng for Xoshiro256StarStar {
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    #[test]
+    fn isaac64_matches_rand_03() {
+        for seed in &[vec![], vec![1], vec![2], vec![7, 8, 9], vec![usize::MAX, 0, 3]] {
+            let mut ours = Isaac64::from_seed(seed);
+            let mut theirs: StdRng = SeedableRng::from_seed(&seed[..]);
+            // Enough to refill the output buffer a few times.
+            for _ in 0..1000 {
+                assert_eq!(ours.next_u64(), theirs.next_u64());
+            }
+            for high in 1..100 {
+                assert_eq!(ours.gen_range(0, high), theirs.gen_range(0, high));
+            }
+            assert_eq!(ours.next_f64(), theirs.next_f64());
+        }
+    }
+
+    #[test]
+    fn xoshiro_reference_output() {
+        // First outputs of the reference implementation from state 1, 2, 3, 4.
+        let mut rng = Xoshiro256StarStar { s: [1, 2, 3, 4] };
+        let words: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+        assert_eq!(words, vec![11520, 0, 1509978240, 1215971899390074240]);
+    }
+
+    #[test]
+    fn xoshiro_is_pinned() {
+        let mut rng = Xoshiro256StarStar::from_seed(&[1]);
+        let words: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            words,
+            vec![18110106563157542208, 8650457082529208451, 3032169436225125478]
+        );
+        assert_ne!(
+            Xoshiro256StarStar::from_seed(&[1]),
+            Xoshiro256StarStar::from_seed(&[1, 0])
+        );
+    }
+
+    #[test]
+    fn gen_range_bounds() {
+        let mut rng = Generator::Xoshiro256.seed(&[4]);
+        for _ in 0..1000 {
+            let x = rng.gen_range(3, 9);
+            assert!((3..9).contains(&x));
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+    }
+}
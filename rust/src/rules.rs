@@ -0,0 +1,370 @@
+use std::fmt;
+use std::str::FromStr;
+
+use std::cmp::Ordering;
+
+use {Card, Comparison, DeckSource, PickupPolicy, Rank, Wild, MAX_MOVES, SUITS_PER_PLAYER};
+
+/// The tunable parts of the game.
+///
+/// `Rules::default()` gives the classic setup; override individual fields to
+/// study variants:
+///
+/// ```
+/// let rules = war::Rules { face_down_per_war: 1, ..war::Rules::default() };
+/// assert_eq!(rules.cards_per_player(), 13 * 256);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Number of tricks played before a game is cut off with
+    /// `Score::FinishWith`.
+    pub move_limit: usize,
+    /// Number of copies of each rank dealt to each player.
+    pub copies_per_rank: usize,
+    /// The ranks in play, from lowest to highest as dealt in a sorted deck.
+    pub ranks: Vec<Rank>,
+    /// Number of jokers dealt to each player on top of the ranks in play.
+    pub jokers: usize,
+    /// Ranks whose cards are wild, like jokers.
+    pub wild_ranks: Vec<Rank>,
+    /// What wild cards do in a trick.
+    pub wild: Wild,
+    /// How the cards turned over in a trick are ranked, wild cards aside.
+    pub comparison: Comparison,
+    /// Whether cards of equal rank are told apart by suit, clubs lowest and
+    /// spades highest, so that only identical cards go to war.
+    pub suits_break_ties: bool,
+    /// Number of cards each player lays face down when a war starts, unless
+    /// `war_depth` says otherwise for the tied rank.
+    pub face_down_per_war: usize,
+    /// How many cards are laid face down in a war, given the tied rank.
+    pub war_depth: WarDepth,
+    /// Number of seats at the table: the computer, the player, then any
+    /// extra players, each dealt a half deck of their own.
+    pub players: usize,
+    /// Where the computer's half deck comes from.
+    pub computer_deck: DeckSource,
+    /// Where the half deck of the player, and of any extra players, comes
+    /// from.
+    pub player_deck: DeckSource,
+    /// How each seat, computer first, picks up the cards it wins. Seats
+    /// past the end of the list use `PickupPolicy::default()`.
+    pub pickup: Vec<PickupPolicy>,
+    /// What happens to a player who cannot lay a full war.
+    pub exhaustion: Exhaustion,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            move_limit: MAX_MOVES,
+            copies_per_rank: SUITS_PER_PLAYER,
+            ranks: Rank::ALL.to_vec(),
+            jokers: 0,
+            wild_ranks: Vec::new(),
+            wild: Wild::default(),
+            comparison: Comparison::default(),
+            suits_break_ties: false,
+            face_down_per_war: 3,
+            war_depth: WarDepth::default(),
+            players: 2,
+            computer_deck: DeckSource::Sorted,
+            player_deck: DeckSource::Dealt,
+            pickup: Vec::new(),
+            exhaustion: Exhaustion::default(),
+        }
+    }
+}
+
+impl Rules {
+    /// Number of cards each player starts with.
+    pub fn cards_per_player(&self) -> usize {
+        self.ranks.len() * self.copies_per_rank + self.jokers
+    }
+
+    /// Whether cards of `rank` are dealt.
+    pub fn is_in_play(&self, rank: Rank) -> bool {
+        if rank == Rank::Joker {
+            self.jokers > 0
+        } else {
+            self.ranks.contains(&rank)
+        }
+    }
+
+    /// Whether `card` is wild.
+    #[inline]
+    pub fn is_wild(&self, card: Card) -> bool {
+        card.is_joker() || (!self.wild_ranks.is_empty() && self.wild_ranks.contains(&card.rank()))
+    }
+
+    /// Whether turning over `card` starts a war under `Wild::StartsWar`.
+    #[inline]
+    pub fn starts_war(&self, card: Card) -> bool {
+        self.wild == Wild::StartsWar && self.is_wild(card)
+    }
+
+    /// The pickup policy of `seat`.
+    pub fn pickup(&self, seat: usize) -> PickupPolicy {
+        self.pickup.get(seat).cloned().unwrap_or_default()
+    }
+
+    /// Whether `compare` gives the same result as `Card::cmp_rank`, which is
+    /// cheaper.
+    pub fn compares_by_rank(&self) -> bool {
+        // Jokers rank above aces, so beating everything else changes nothing.
+        self.comparison == Comparison::Standard
+            && !self.suits_break_ties
+            && self.wild_ranks.is_empty()
+            && self.wild == Wild::Beats
+    }
+
+    /// Compare two cards turned over in a trick, `Greater` meaning `a` wins.
+    /// A wild card under `Wild::StartsWar` ties with anything, whatever the
+    /// suits.
+    #[inline]
+    pub fn compare(&self, a: Card, b: Card) -> Ordering {
+        let ordering = match (self.is_wild(a), self.is_wild(b)) {
+            (false, false) => self.comparison.compare(a, b, &self.ranks),
+            _ if self.wild == Wild::StartsWar => return Ordering::Equal,
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+        };
+        if self.suits_break_ties {
+            ordering.then(a.suit().cmp(&b.suit()))
+        } else {
+            ordering
+        }
+    }
+
+    /// How many of the ranks in play `card` beats, which orders cards from
+    /// weakest to strongest even when the comparison is not transitive.
+    pub fn strength(&self, card: Card) -> usize {
+        let beats = |&&rank: &&Rank| self.compare(card, Card::new(rank, card.suit())) == Ordering::Greater;
+        self.ranks.iter().filter(beats).count()
+    }
+
+    /// Number of cards each player lays face down in a war over `rank`.
+    pub fn face_down(&self, rank: Rank) -> usize {
+        self.war_depth.face_down(rank).unwrap_or(self.face_down_per_war)
+    }
+
+    /// Whether every game played by these rules always plays out the same
+    /// way from the same position, so that it can be checked for loops.
+    pub fn is_deterministic(&self) -> bool {
+        !self.pickup.iter().any(PickupPolicy::is_random) && !self.exhaustion.is_random()
+    }
+}
+
+/// What happens when players at war hold too few cards to lay all their
+/// face-down cards and turn over a new card.
+///
+/// A player is short when they hold fewer cards than the war asks them to
+/// lay face down, plus one to turn over, as the war starts. Whatever the
+/// policy, a player who has no card left to turn over is knocked out, and
+/// when every player at war is short they lay what they can as for `Skip`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Exhaustion {
+    /// Short players lay as many face-down cards as they can and are
+    /// knocked out if they have no card left to turn over.
+    #[default]
+    Skip,
+    /// Short players keep their last card back to turn over, and if they
+    /// have already laid it, their last face-down card is turned over in
+    /// its place.
+    LastCard,
+    /// Short players are knocked out as soon as the war starts, leaving
+    /// their cards on the table.
+    LoseImmediately,
+    /// Short players sit the war out, keeping their cards, and the others
+    /// play it alone. A player left alone takes the trick.
+    PlayAlone,
+    /// Every card on the table is shuffled, by a generator seeded from the
+    /// given word and the number of tricks played, and handed back, each
+    /// player getting as many as they laid on top of their deck. The trick
+    /// is then played again, without reshuffling a second time.
+    Reshuffle(usize),
+}
+
+impl Exhaustion {
+    /// Whether the policy shuffles. Games using it cannot loop, as for
+    /// `PickupPolicy::is_random`.
+    pub fn is_random(&self) -> bool {
+        matches!(*self, Exhaustion::Reshuffle(_))
+    }
+}
+
+impl FromStr for Exhaustion {
+    type Err = String;
+
+    /// Parse `skip`, `last-card`, `lose`, `play-alone` or `reshuffle:SEED`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "skip" => Ok(Exhaustion::Skip),
+            "last-card" => Ok(Exhaustion::LastCard),
+            "lose" => Ok(Exhaustion::LoseImmediately),
+            "play-alone" => Ok(Exhaustion::PlayAlone),
+            _ => match s.strip_prefix("reshuffle:").map(str::parse) {
+                Some(Ok(seed)) => Ok(Exhaustion::Reshuffle(seed)),
+                _ => Err(format!(
+                    "unknown exhaustion policy {:?}, expected skip, last-card, lose, \
+                     play-alone or reshuffle:SEED",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Exhaustion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Exhaustion::Skip => f.write_str("skip"),
+            Exhaustion::LastCard => f.write_str("last-card"),
+            Exhaustion::LoseImmediately => f.write_str("lose"),
+            Exhaustion::PlayAlone => f.write_str("play-alone"),
+            Exhaustion::Reshuffle(seed) => write!(f, "reshuffle:{}", seed),
+        }
+    }
+}
+
+/// The number of face-down cards laid in a war, as a function of the rank
+/// the players tied on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum WarDepth {
+    /// Always `Rules::face_down_per_war`.
+    #[default]
+    Fixed,
+    /// The value of the card as in blackjack: number cards their face
+    /// value, jacks, queens, kings and jokers 10 and aces 11.
+    CardValue,
+    /// The given count for each listed rank, and
+    /// `Rules::face_down_per_war` for the others.
+    Ranks(Vec<(Rank, usize)>),
+}
+
+impl WarDepth {
+    /// The number of face-down cards in a war over `rank`, or `None` to
+    /// use `Rules::face_down_per_war`.
+    pub fn face_down(&self, rank: Rank) -> Option<usize> {
+        match *self {
+            WarDepth::Fixed => None,
+            WarDepth::CardValue => Some(match rank {
+                Rank::Jack | Rank::Queen | Rank::King | Rank::Joker => 10,
+                Rank::Ace => 11,
+                rank => rank.value() as usize,
+            }),
+            WarDepth::Ranks(ref counts) => counts.iter().find(|&&(r, _)| r == rank).map(|&(_, count)| count),
+        }
+    }
+}
+
+impl FromStr for WarDepth {
+    type Err = String;
+
+    /// Parse `fixed`, `card-value` or comma separated `RANK=COUNT` pairs
+    /// such as `7=7,J=10`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "fixed" => Ok(WarDepth::Fixed),
+            "card-value" => Ok(WarDepth::CardValue),
+            _ => s
+                .split(',')
+                .map(|pair| {
+                    let mut parts = pair.splitn(2, '=');
+                    let rank = parts.next().unwrap_or("").parse()?;
+                    let count = parts.next().and_then(|count| count.parse().ok());
+                    count.map(|count| (rank, count)).ok_or_else(|| {
+                        format!(
+                            "invalid war depth {:?}, expected fixed, card-value or RANK=COUNT pairs",
+                            s
+                        )
+                    })
+                })
+                .collect::<Result<_, String>>()
+                .map(WarDepth::Ranks),
+        }
+    }
+}
+
+impl fmt::Display for WarDepth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WarDepth::Fixed => f.write_str("fixed"),
+            WarDepth::CardValue => f.write_str("card-value"),
+            WarDepth::Ranks(ref counts) => {
+                for (i, &(rank, count)) in counts.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}={}", rank, count)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exhaustion_notation() {
+        for &policy in &[
+            Exhaustion::Skip,
+            Exhaustion::LastCard,
+            Exhaustion::LoseImmediately,
+            Exhaustion::PlayAlone,
+            Exhaustion::Reshuffle(5),
+        ] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        assert!("reshuffle".parse::<Exhaustion>().is_err());
+    }
+
+    #[test]
+    fn suits_break_ties() {
+        let rules = Rules {
+            comparison: Comparison::Peace,
+            suits_break_ties: true,
+            ..Rules::default()
+        };
+        let card = |card: &str| card.parse::<Card>().unwrap();
+        assert_eq!(rules.compare(card("7S"), card("7C")), Ordering::Greater);
+        assert_eq!(rules.compare(card("7S"), card("8C")), Ordering::Greater);
+        assert_eq!(rules.compare(card("7D"), card("7D")), Ordering::Equal);
+        assert_eq!(Rules::default().compare(card("7S"), card("7C")), Ordering::Equal);
+
+        let rules = Rules { wild: Wild::StartsWar, ..rules };
+        assert_eq!(rules.compare(card("JKC"), card("9S")), Ordering::Equal);
+        assert_eq!(rules.compare(card("9S"), card("JKC")), Ordering::Equal);
+        assert_eq!(rules.compare(card("JKC"), card("JKD")), Ordering::Equal);
+    }
+
+    #[test]
+    fn war_depth() {
+        let rules = Rules {
+            war_depth: "7=7,J=10".parse().unwrap(),
+            ..Rules::default()
+        };
+        assert_eq!(rules.face_down(Rank::Seven), 7);
+        assert_eq!(rules.face_down(Rank::Jack), 10);
+        assert_eq!(rules.face_down(Rank::Two), 3);
+
+        let rules = Rules {
+            war_depth: WarDepth::CardValue,
+            ..rules
+        };
+        assert_eq!(rules.face_down(Rank::Seven), 7);
+        assert_eq!(rules.face_down(Rank::King), 10);
+        assert_eq!(rules.face_down(Rank::Ace), 11);
+
+        for depth in &["fixed", "card-value", "7=7,J=10"] {
+            assert_eq!(depth.parse::<WarDepth>().unwrap().to_string(), *depth);
+        }
+        assert!("7".parse::<WarDepth>().is_err());
+        assert!("X=2".parse::<WarDepth>().is_err());
+        assert!("".parse::<WarDepth>().is_err());
+    }
+}
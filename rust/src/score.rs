@@ -0,0 +1,141 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use Rules;
+
+/// The result of a finished game, from the player's point of view.
+///
+/// Scores are ranked by `Score::compare` from worst to best for the player:
+/// quick losses, slow losses, games cut off at the move limit (holding more
+/// cards is better), slow wins and finally quick wins. Ties and cycles rank
+/// as a game cut off with the player holding a half deck, so the ranking
+/// depends on the rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Score {
+    /// The computer ran out of cards after the given number of moves.
+    WinAfter(usize),
+    /// The player ran out of cards after the given number of moves.
+    LoseAfter(usize),
+    /// The move limit was reached with the player holding this many cards.
+    FinishWith(usize),
+    /// Both players ran out of cards during a war at the given move.
+    TiedAt(usize),
+    /// The game returned to an earlier position and will loop forever. The
+    /// loop is first entered after `entered_at` moves and repeats every
+    /// `period` moves.
+    Cycle { period: usize, entered_at: usize },
+}
+
+impl Score {
+    /// Sort key implementing the documented order under `rules`. Among ties,
+    /// cycles and the finish they rank as, which are equally good for the
+    /// player, the order is arbitrary but total.
+    fn key(&self, rules: &Rules) -> (u8, usize, u8, usize, usize) {
+        let half = rules.cards_per_player();
+        match *self {
+            Score::LoseAfter(moves) => (0, moves, 0, 0, 0),
+            Score::TiedAt(moves) => (1, half, 0, moves, 0),
+            Score::Cycle { period, entered_at } => (1, half, 1, entered_at, period),
+            Score::FinishWith(cards) => (1, cards, 2, 0, 0),
+            Score::WinAfter(moves) => (2, usize::MAX - moves, 0, 0, 0),
+        }
+    }
+
+    /// Rank two scores from games played under `rules`, `Greater` meaning
+    /// `self` is better for the player.
+    pub fn compare(&self, other: &Score, rules: &Rules) -> Ordering {
+        self.key(rules).cmp(&other.key(rules))
+    }
+
+    /// A short lowercase name for the kind of result, for machine-readable
+    /// output.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Score::WinAfter(_) => "win",
+            Score::LoseAfter(_) => "loss",
+            Score::FinishWith(_) => "finish",
+            Score::TiedAt(_) => "tie",
+            Score::Cycle { .. } => "cycle",
+        }
+    }
+
+    /// Encode the score as a single number where larger is better for the
+    /// player, for searches that need a numeric fitness.
+    ///
+    /// The encoding agrees with `Score::compare` (a worse score never
+    /// encodes higher), but ties and cycles share the value of a finish
+    /// holding a half deck. It depends on the move limit and deck size, so
+    /// numbers are only comparable when produced under the same `rules`.
+    pub fn to_int(&self, rules: &Rules) -> usize {
+        let max_moves = rules.move_limit;
+        let cards = rules.cards_per_player();
+        match *self {
+            Score::LoseAfter(moves) => moves,
+            Score::TiedAt(_) | Score::Cycle { .. } => max_moves + cards,
+            Score::FinishWith(player_cards) => max_moves + player_cards,
+            Score::WinAfter(moves) => max_moves + (cards * 2) + max_moves.saturating_sub(moves),
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Score::WinAfter(moves) => write!(f, "win after {} moves", moves),
+            Score::LoseAfter(moves) => write!(f, "loss after {} moves", moves),
+            Score::FinishWith(cards) => write!(f, "cut off holding {} cards", cards),
+            Score::TiedAt(moves) => write!(f, "tie after {} moves", moves),
+            Score::Cycle { period, entered_at } => {
+                write!(f, "cycle of {} moves from move {}", period, entered_at)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Score::*;
+
+    #[test]
+    fn ordering() {
+        let ordered = [
+            LoseAfter(10),
+            LoseAfter(500),
+            FinishWith(0),
+            FinishWith(100),
+            TiedAt(20),
+            Cycle { period: 60, entered_at: 24 },
+            FinishWith(3328),
+            FinishWith(6000),
+            WinAfter(5000),
+            WinAfter(10),
+        ];
+
+        let rules = Rules::default();
+        for pair in ordered.windows(2) {
+            assert_eq!(pair[0].compare(&pair[1], &rules), Ordering::Less, "{:?} < {:?}", pair[0], pair[1]);
+            assert!(pair[0].to_int(&rules) <= pair[1].to_int(&rules));
+        }
+        assert_eq!(TiedAt(20).to_int(&rules), FinishWith(3328).to_int(&rules));
+    }
+
+    #[test]
+    fn to_int_matches_the_original_encoding() {
+        let rules = Rules::default();
+        assert_eq!(LoseAfter(7).to_int(&rules), 7);
+        assert_eq!(TiedAt(7).to_int(&rules), 1_003_328);
+        assert_eq!(FinishWith(100).to_int(&rules), 1_000_100);
+        assert_eq!(WinAfter(226_918).to_int(&rules), 1_779_738);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(WinAfter(3).to_string(), "win after 3 moves");
+        assert_eq!(FinishWith(40).to_string(), "cut off holding 40 cards");
+        assert_eq!(
+            Cycle { period: 60, entered_at: 24 }.to_string(),
+            "cycle of 60 moves from move 24"
+        );
+    }
+}
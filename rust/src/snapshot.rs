@@ -0,0 +1,422 @@
+use std::error::Error;
+use std::fmt;
+
+use replay::deck_key;
+use {Card, Deck, GameState, Rank, Rules};
+
+/// The version written into snapshots. Snapshots with any other version are
+/// rejected rather than misread.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The first line of a text snapshot, followed by the version.
+const TEXT_MAGIC: &str = "war-state";
+
+/// The first bytes of a binary snapshot, followed by the version byte.
+const BINARY_MAGIC: &[u8; 4] = b"WARS";
+
+/// Why a snapshot could not be restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The snapshot is malformed.
+    Parse(String),
+    /// The snapshot was written with another format version.
+    Version(u32),
+    /// The game has a different number of seats than the rules.
+    Players { expected: usize, found: usize },
+    /// A card has a rank that is not in play under the rules.
+    InvalidRank(Rank),
+    /// The decks together do not hold as many cards of a rank as are dealt
+    /// under the rules.
+    RankCount { rank: Rank, expected: usize, found: usize },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SnapshotError::Parse(ref message) => f.write_str(message),
+            SnapshotError::Version(version) => write!(
+                f,
+                "unsupported snapshot version {}, expected {}",
+                version, SNAPSHOT_VERSION
+            ),
+            SnapshotError::Players { expected, found } => {
+                write!(f, "expected {} players, found {}", expected, found)
+            }
+            SnapshotError::InvalidRank(rank) => write!(f, "rank {} is not in play", rank),
+            SnapshotError::RankCount {
+                rank,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {} cards of rank {} between all decks, found {}",
+                expected, rank, found
+            ),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+fn parse_error<T>(message: String) -> Result<T, SnapshotError> {
+    Err(SnapshotError::Parse(message))
+}
+
+/// Snapshots save a game between tricks so that it can be resumed later or
+/// by another tool.
+///
+/// The text form is line based:
+///
+/// ```text
+/// war-state 1
+/// moves 84
+/// wars 5
+/// out 2 61
+/// computer 2C AD 7S
+/// player 9H KC 4D
+/// player-3
+/// ```
+///
+/// with the seats knocked out as seat and move pairs, then each seat's deck
+/// in card notation. The binary form is `WARS`, a version byte, the moves
+/// and wars as little-endian `u64`s, the number of seats and of knocked out
+/// seats as little-endian `u32`s, each knocked out seat as a `u32` seat and
+/// a `u64` move, then each deck in seat order as a `u32` length followed by
+/// each card packed as by `Card::to_byte`.
+///
+/// Restoring a snapshot checks it against the rules: the game must have
+/// `rules.players` seats, every card must have a rank in play, and between
+/// them the decks must hold exactly the cards the rules deal, suits
+/// included, since no cards are created or lost between tricks.
+impl GameState {
+    /// The text form of the game.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{} {}\nmoves {}\nwars {}\nout",
+            TEXT_MAGIC,
+            SNAPSHOT_VERSION,
+            self.moves(),
+            self.wars()
+        );
+        for &(seat, moves) in self.knocked_out() {
+            text += &format!(" {} {}", seat, moves);
+        }
+        text.push('\n');
+        for (seat, deck) in self.decks().iter().enumerate() {
+            if deck.is_empty() {
+                text += &format!("{}\n", deck_key(seat));
+            } else {
+                text += &format!("{} {}\n", deck_key(seat), deck);
+            }
+        }
+        text
+    }
+
+    /// Restore a game from its text form.
+    pub fn from_text(text: &str, rules: &Rules) -> Result<GameState, SnapshotError> {
+        let mut lines = text.lines();
+        // The rest of the next line, which must start with `key`.
+        let mut field = |key: &str| -> Result<&str, SnapshotError> {
+            let line = match lines.next() {
+                Some(line) => line.trim(),
+                None => return parse_error(format!("missing {} line", key)),
+            };
+            let mut parts = line.splitn(2, char::is_whitespace);
+            match parts.next() {
+                Some(found) if found == key => Ok(parts.next().unwrap_or("").trim()),
+                found => parse_error(format!("expected {} line, found {:?}", key, found.unwrap_or(""))),
+            }
+        };
+        fn number<T: ::std::str::FromStr>(key: &str, value: &str) -> Result<T, SnapshotError> {
+            value
+                .parse()
+                .or_else(|_| parse_error(format!("invalid {} {:?}", key, value)))
+        }
+        fn deck(key: &str, value: &str) -> Result<Deck, SnapshotError> {
+            value
+                .parse()
+                .or_else(|error| parse_error(format!("invalid {} deck: {}", key, error)))
+        }
+
+        let version = number(TEXT_MAGIC, field(TEXT_MAGIC)?)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::Version(version));
+        }
+        let moves = number("moves", field("moves")?)?;
+        let wars = number("wars", field("wars")?)?;
+        let out: Vec<usize> = field("out")?
+            .split_whitespace()
+            .map(|word| number("out", word))
+            .collect::<Result<_, _>>()?;
+        if !out.len().is_multiple_of(2) {
+            return parse_error("expected seat and move pairs on the out line".to_string());
+        }
+        // One deck per remaining line, in seat order.
+        let mut decks = Vec::new();
+        for (seat, line) in lines.filter(|line| !line.trim().is_empty()).enumerate() {
+            let key = deck_key(seat);
+            let mut parts = line.trim().splitn(2, char::is_whitespace);
+            if parts.next() != Some(&key[..]) {
+                return parse_error(format!("expected {} line, found {:?}", key, line));
+            }
+            decks.push(deck(&key, parts.next().unwrap_or("").trim())?);
+        }
+
+        let out: Vec<(usize, usize)> = out.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+        let game = restore(decks, out, moves, wars)?;
+        game.validate(rules)?;
+        Ok(game)
+    }
+
+    /// The binary form of the game.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let cards: usize = self.decks().iter().map(Deck::len).sum();
+        let header = 29 + 12 * self.knocked_out().len() + 4 * self.players();
+        let mut bytes = Vec::with_capacity(header + cards);
+        bytes.extend_from_slice(BINARY_MAGIC);
+        bytes.push(SNAPSHOT_VERSION as u8);
+        bytes.extend_from_slice(&(self.moves() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.wars() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.players() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.knocked_out().len() as u32).to_le_bytes());
+        for &(seat, moves) in self.knocked_out() {
+            bytes.extend_from_slice(&(seat as u32).to_le_bytes());
+            bytes.extend_from_slice(&(moves as u64).to_le_bytes());
+        }
+        for deck in self.decks() {
+            bytes.extend_from_slice(&(deck.len() as u32).to_le_bytes());
+            bytes.extend(deck.iter().map(|card| card.to_byte()));
+        }
+        bytes
+    }
+
+    /// Restore a game from its binary form.
+    pub fn from_bytes(bytes: &[u8], rules: &Rules) -> Result<GameState, SnapshotError> {
+        fn take<'a>(rest: &mut &'a [u8], n: usize) -> Result<&'a [u8], SnapshotError> {
+            if rest.len() < n {
+                return parse_error("snapshot is truncated".to_string());
+            }
+            let (taken, remaining) = rest.split_at(n);
+            *rest = remaining;
+            Ok(taken)
+        }
+        fn word(bytes: &[u8]) -> usize {
+            let mut word = [0; 8];
+            word[..bytes.len()].copy_from_slice(bytes);
+            u64::from_le_bytes(word) as usize
+        }
+
+        let mut rest = bytes;
+        if take(&mut rest, 4)? != BINARY_MAGIC {
+            return parse_error("not a binary snapshot".to_string());
+        }
+        let version = take(&mut rest, 1)?[0] as u32;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::Version(version));
+        }
+        let moves = word(take(&mut rest, 8)?);
+        let wars = word(take(&mut rest, 8)?);
+        let players = word(take(&mut rest, 4)?);
+        let knocked_out = word(take(&mut rest, 4)?);
+        let mut out = Vec::new();
+        for _ in 0..knocked_out {
+            let seat = word(take(&mut rest, 4)?);
+            out.push((seat, word(take(&mut rest, 8)?)));
+        }
+        let mut decks = Vec::new();
+        for _ in 0..players {
+            let len = word(take(&mut rest, 4)?);
+            let cards: Option<Vec<Card>> = take(&mut rest, len)?.iter().map(|&byte| Card::from_byte(byte)).collect();
+            match cards {
+                Some(cards) => decks.push(Deck::from_vec(cards)),
+                None => return parse_error("invalid card in snapshot".to_string()),
+            }
+        }
+        if !rest.is_empty() {
+            return parse_error(format!("{} unexpected bytes after the snapshot", rest.len()));
+        }
+
+        let game = restore(decks, out, moves, wars)?;
+        game.validate(rules)?;
+        Ok(game)
+    }
+
+    /// Check that the game could have been reached under `rules`: it has as
+    /// many seats as the rules, every card has a rank in play, and no card
+    /// has been created or lost. Cards are counted by rank, as
+    /// `DeckSource::check` does, since decks given as cards may use any
+    /// suits.
+    pub fn validate(&self, rules: &Rules) -> Result<(), SnapshotError> {
+        if self.players() != rules.players {
+            return Err(SnapshotError::Players {
+                expected: rules.players,
+                found: self.players(),
+            });
+        }
+        let mut counts = [0; 16];
+        for &card in self.decks().iter().flat_map(|deck| deck.iter()) {
+            if !rules.is_in_play(card.rank()) {
+                return Err(SnapshotError::InvalidRank(card.rank()));
+            }
+            counts[card.rank().value() as usize] += 1;
+        }
+        let mut expected = [0; 16];
+        for &card in Deck::new_half_deck(rules).iter() {
+            expected[card.rank().value() as usize] += rules.players;
+        }
+        for &rank in Rank::ALL.iter().chain(&[Rank::Joker]) {
+            let value = rank.value() as usize;
+            if counts[value] != expected[value] {
+                return Err(SnapshotError::RankCount {
+                    rank,
+                    expected: expected[value],
+                    found: counts[value],
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild a game from its parts, checking what `GameState::from_seats`
+/// asserts.
+fn restore(
+    decks: Vec<Deck>,
+    out: Vec<(usize, usize)>,
+    moves: usize,
+    wars: usize,
+) -> Result<GameState, SnapshotError> {
+    if decks.len() < 2 {
+        return parse_error("a game needs at least two players".to_string());
+    }
+    for (i, &(seat, _)) in out.iter().enumerate() {
+        if seat >= decks.len() || out[..i].iter().any(|&(other, _)| other == seat) {
+            return parse_error(format!("invalid knocked out seat {}", seat));
+        }
+    }
+    Ok(GameState::from_seats(decks, out, moves, wars))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Generator;
+
+    fn rules() -> Rules {
+        Rules {
+            copies_per_rank: 2,
+            ..Rules::default()
+        }
+    }
+
+    fn game() -> GameState {
+        let rules = rules();
+        let mut game = GameState::new(&rules, &mut Generator::Xoshiro256.seed(&[9]));
+        while game.wars() == 0 {
+            game = match game.step(&rules) {
+                ::GameStepped::Cont(game) => game,
+                ::GameStepped::Done(score) => panic!("game over early: {}", score),
+            };
+        }
+        game
+    }
+
+    #[test]
+    fn round_trips() {
+        let game = game();
+        assert_eq!(GameState::from_text(&game.to_text(), &rules()), Ok(game.clone()));
+        assert_eq!(GameState::from_bytes(&game.to_bytes(), &rules()), Ok(game));
+    }
+
+    #[test]
+    fn round_trips_with_more_players() {
+        let rules = Rules {
+            players: 4,
+            ..rules()
+        };
+        let mut game = GameState::new(&rules, &mut Generator::Xoshiro256.seed(&[4]));
+        while game.knocked_out().is_empty() {
+            game = match game.step(&rules) {
+                ::GameStepped::Cont(game) => game,
+                ::GameStepped::Done(score) => panic!("game over early: {}", score),
+            };
+        }
+        assert_eq!(GameState::from_text(&game.to_text(), &rules), Ok(game.clone()));
+        assert_eq!(GameState::from_bytes(&game.to_bytes(), &rules), Ok(game.clone()));
+        assert_eq!(
+            GameState::from_text(&game.to_text(), &self::rules()),
+            Err(SnapshotError::Players { expected: 2, found: 4 })
+        );
+    }
+
+    #[test]
+    fn round_trips_with_a_deck_of_cards() {
+        let rules = Rules {
+            computer_deck: "cards:[2 3 4 5 6 7 8 9 T J Q K A]*2".parse().unwrap(),
+            ..rules()
+        };
+        assert_eq!(rules.computer_deck.check(&rules), Ok(()));
+        let mut game = GameState::new(&rules, &mut Generator::Xoshiro256.seed(&[3]));
+        for _ in 0..5 {
+            game = match game.step(&rules) {
+                ::GameStepped::Cont(game) => game,
+                ::GameStepped::Done(score) => panic!("game over early: {}", score),
+            };
+        }
+        assert_eq!(GameState::from_text(&game.to_text(), &rules), Ok(game.clone()));
+        assert_eq!(GameState::from_bytes(&game.to_bytes(), &rules), Ok(game));
+    }
+
+    #[test]
+    fn text_form() {
+        let rules = Rules {
+            copies_per_rank: 2,
+            ranks: vec![Rank::Two, Rank::Three],
+            ..Rules::default()
+        };
+        let deck = |cards: &str| cards.parse::<Deck>().unwrap();
+        let game = GameState::from_parts(deck("3C 2D 2C"), deck("3D 2C 3C 2D 3D"), 4, 1);
+        let text = "war-state 1\nmoves 4\nwars 1\nout\ncomputer 3C 2D 2C\nplayer 3D 2C 3C 2D 3D\n";
+        assert_eq!(game.to_text(), text);
+        assert_eq!(GameState::from_text(text, &rules), Ok(game));
+
+        let lost = "war-state 1\nmoves 4\nwars 1\nout\ncomputer 3C 2D 2C\nplayer 3D 2C 3C 2D\n";
+        assert_eq!(
+            GameState::from_text(lost, &rules),
+            Err(SnapshotError::RankCount {
+                rank: Rank::Three,
+                expected: 4,
+                found: 3
+            })
+        );
+        let invalid = "war-state 1\nmoves 4\nwars 1\nout\ncomputer 3C 2D 2C\nplayer 3D 2C 3C 2D 3D 4C\n";
+        assert_eq!(GameState::from_text(invalid, &rules), Err(SnapshotError::InvalidRank(Rank::Four)));
+        assert!(GameState::from_text(&invalid.replace("4C", "15"), &rules).is_err());
+        let jokers = "war-state 1\nmoves 4\nwars 1\nout\ncomputer 3C 2D 2C JK\nplayer 3D 2C 3C 2D 3D JK\n";
+        assert_eq!(GameState::from_text(jokers, &rules), Err(SnapshotError::InvalidRank(Rank::Joker)));
+        assert!(GameState::from_text(jokers, &Rules { jokers: 1, ..rules.clone() }).is_ok());
+        assert_eq!(
+            GameState::from_text("war-state 2\n", &rules),
+            Err(SnapshotError::Version(2))
+        );
+        assert!(GameState::from_text("war-state 1\nmoves x\n", &rules).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_bytes() {
+        let game = game();
+        let bytes = game.to_bytes();
+        for len in 0..bytes.len() {
+            assert!(GameState::from_bytes(&bytes[..len], &rules()).is_err());
+        }
+        let mut extra = bytes.clone();
+        extra.push(2);
+        assert!(GameState::from_bytes(&extra, &rules()).is_err());
+        let mut card = bytes.clone();
+        card[37] = 0;
+        assert!(GameState::from_bytes(&card, &rules()).is_err());
+        let mut version = bytes;
+        version[4] = 7;
+        assert_eq!(GameState::from_bytes(&version, &rules()), Err(SnapshotError::Version(7)));
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+use std::str::FromStr;
+
+use {Card, Deck, Rank, Rules, Suit, WarRng, Xoshiro256StarStar};
+
+/// Where a seat's half deck comes from when a game is dealt.
+///
+/// `Rules::computer_deck` deals the computer's deck and
+/// `Rules::player_deck` every other seat's, so experiments can pit the
+/// player against any fixed opponent. The patterns keep any jokers after
+/// the other cards, as `Deck::new_half_deck` does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeckSource {
+    /// `Deck::new_half_deck`, the computer's classic deck.
+    Sorted,
+    /// The sorted half deck upside down, highest card first.
+    Reversed,
+    /// Each run of the sorted half deck alternating between its highest and
+    /// lowest remaining rank, as in `A 2 K 3 Q 4`.
+    HighLow,
+    /// The sorted half deck grouped by rank: every copy of the lowest rank,
+    /// then every copy of the next.
+    ByRank,
+    /// Shuffled by the generator the game is dealt from, seat by seat in
+    /// order, the player's classic deck.
+    Dealt,
+    /// Shuffled by a generator seeded with the given word alone, so the deck
+    /// stays the same whatever seed the game is dealt from.
+    Shuffled(usize),
+    /// The given cards, which should be a reordered half deck.
+    Cards(Deck),
+}
+
+impl DeckSource {
+    /// Deal a half deck under `rules`, drawing on `rng` for `Dealt`.
+    pub fn deal<R: WarRng + ?Sized>(&self, rules: &Rules, rng: &mut R) -> Deck {
+        match *self {
+            DeckSource::Sorted => Deck::new_half_deck(rules),
+            DeckSource::Reversed => {
+                let mut cards = Deck::new_half_deck(rules).to_vec();
+                cards.reverse();
+                Deck::from_vec(cards)
+            }
+            DeckSource::HighLow => {
+                let mut order = Vec::with_capacity(rules.ranks.len());
+                let (mut low, mut high) = (0, rules.ranks.len());
+                while low < high {
+                    high -= 1;
+                    order.push(rules.ranks[high]);
+                    if low < high {
+                        order.push(rules.ranks[low]);
+                        low += 1;
+                    }
+                }
+                let mut cards = Vec::with_capacity(rules.cards_per_player());
+                for copy in 0..rules.copies_per_rank {
+                    let suit = Suit::ALL[copy % Suit::ALL.len()];
+                    cards.extend(order.iter().map(|&rank| Card::new(rank, suit)));
+                }
+                cards.extend(jokers(rules));
+                Deck::from_vec(cards)
+            }
+            DeckSource::ByRank => {
+                let mut cards = Vec::with_capacity(rules.cards_per_player());
+                for &rank in &rules.ranks {
+                    for copy in 0..rules.copies_per_rank {
+                        cards.push(Card::new(rank, Suit::ALL[copy % Suit::ALL.len()]));
+                    }
+                }
+                cards.extend(jokers(rules));
+                Deck::from_vec(cards)
+            }
+            DeckSource::Dealt => Deck::new_shuffle(rules, rng),
+            DeckSource::Shuffled(seed) => Deck::new_shuffle(rules, &mut Xoshiro256StarStar::from_seed(&[seed])),
+            DeckSource::Cards(ref deck) => deck.clone(),
+        }
+    }
+
+    /// Check that the source deals a half deck under `rules`, comparing ranks
+    /// only so that cards written without suits pass. Only `Cards` can fail.
+    pub fn check(&self, rules: &Rules) -> Result<(), String> {
+        if let DeckSource::Cards(ref deck) = *self {
+            let sorted = |deck: &Deck| {
+                let mut ranks: Vec<Rank> = deck.iter().map(|card| card.rank()).collect();
+                ranks.sort_unstable();
+                ranks
+            };
+            if sorted(deck) != sorted(&Deck::new_half_deck(rules)) {
+                return Err(format!(
+                    "the deck is not a reordered half deck of {} cards under these rules",
+                    rules.cards_per_player()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The jokers at the end of `Deck::new_half_deck`.
+fn jokers(rules: &Rules) -> impl Iterator<Item = Card> {
+    (0..rules.jokers).map(|joker| Card::new(Rank::Joker, Suit::ALL[joker % Suit::ALL.len()]))
+}
+
+impl FromStr for DeckSource {
+    type Err = String;
+
+    /// Parse `sorted`, `reverse`, `high-low`, `by-rank`, `dealt`,
+    /// `shuffled:SEED` or `cards:` followed by a deck in card notation.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "sorted" => Ok(DeckSource::Sorted),
+            "reverse" => Ok(DeckSource::Reversed),
+            "high-low" => Ok(DeckSource::HighLow),
+            "by-rank" => Ok(DeckSource::ByRank),
+            "dealt" => Ok(DeckSource::Dealt),
+            _ => {
+                if let Some(cards) = s.strip_prefix("cards:") {
+                    return cards.parse().map(DeckSource::Cards);
+                }
+                match s.strip_prefix("shuffled:").map(str::parse) {
+                    Some(Ok(seed)) => Ok(DeckSource::Shuffled(seed)),
+                    _ => Err(format!(
+                        "unknown deck source {:?}, expected sorted, reverse, high-low, by-rank, \
+                         dealt, shuffled:SEED or cards:DECK",
+                        s
+                    )),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DeckSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeckSource::Sorted => f.write_str("sorted"),
+            DeckSource::Reversed => f.write_str("reverse"),
+            DeckSource::HighLow => f.write_str("high-low"),
+            DeckSource::ByRank => f.write_str("by-rank"),
+            DeckSource::Dealt => f.write_str("dealt"),
+            DeckSource::Shuffled(seed) => write!(f, "shuffled:{}", seed),
+            DeckSource::Cards(ref deck) => write!(f, "cards:{}", deck),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Generator;
+
+    fn deal(source: &DeckSource, rules: &Rules) -> String {
+        source.deal(rules, &mut *Generator::Xoshiro256.seed(&[1])).to_string()
+    }
+
+    #[test]
+    fn patterns() {
+        let rules = Rules {
+            copies_per_rank: 2,
+            ranks: vec![Rank::Two, Rank::Three, Rank::Four, Rank::Five, Rank::Ace],
+            jokers: 1,
+            ..Rules::default()
+        };
+        assert_eq!(deal(&DeckSource::Sorted, &rules), "2C 3C 4C 5C AC 2D 3D 4D 5D AD JKC");
+        assert_eq!(deal(&DeckSource::Reversed, &rules), "JKC AD 5D 4D 3D 2D AC 5C 4C 3C 2C");
+        assert_eq!(deal(&DeckSource::HighLow, &rules), "AC 2C 5C 3C 4C AD 2D 5D 3D 4D JKC");
+        assert_eq!(deal(&DeckSource::ByRank, &rules), "2C 2D 3C 3D 4C 4D 5C 5D AC AD JKC");
+        for source in &[
+            DeckSource::Sorted,
+            DeckSource::Reversed,
+            DeckSource::HighLow,
+            DeckSource::ByRank,
+            DeckSource::Dealt,
+            DeckSource::Shuffled(3),
+        ] {
+            assert_eq!(source.check(&rules), Ok(()), "{}", source);
+            let cards = DeckSource::Cards(deal(source, &rules).parse().unwrap());
+            assert_eq!(cards.check(&rules), Ok(()), "{}", source);
+        }
+        assert!(DeckSource::Cards("2 3 4".parse().unwrap()).check(&rules).is_err());
+        let suitless = DeckSource::Cards("[2 3 4 5 A]*2 JK".parse().unwrap());
+        assert_eq!(suitless.check(&rules), Ok(()));
+        assert!(DeckSource::Cards("[2 3 4 5 5]*2 JK".parse().unwrap()).check(&rules).is_err());
+    }
+
+    #[test]
+    fn shuffled_ignores_the_game_seed() {
+        let rules = Rules {
+            copies_per_rank: 1,
+            ..Rules::default()
+        };
+        let shuffled = DeckSource::Shuffled(4);
+        let other = shuffled.deal(&rules, &mut *Generator::Xoshiro256.seed(&[2]));
+        assert_eq!(deal(&shuffled, &rules), other.to_string());
+        assert_ne!(deal(&DeckSource::Shuffled(5), &rules), other.to_string());
+        assert_ne!(deal(&DeckSource::Dealt, &rules), other.to_string());
+    }
+
+    #[test]
+    fn notation() {
+        for source in &[
+            DeckSource::Sorted,
+            DeckSource::Reversed,
+            DeckSource::HighLow,
+            DeckSource::ByRank,
+            DeckSource::Dealt,
+            DeckSource::Shuffled(8),
+            DeckSource::Cards("AS KH [2 3]*2".parse().unwrap()),
+        ] {
+            assert_eq!(source.to_string().parse().as_ref(), Ok(source));
+        }
+        assert!("shuffled".parse::<DeckSource>().is_err());
+        assert!("cards:X".parse::<DeckSource>().is_err());
+    }
+}
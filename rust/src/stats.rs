@@ -0,0 +1,151 @@
+use std::fmt;
+
+use {Outcome, Score};
+
+/// Running totals of move counts for one kind of result.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub games: usize,
+    pub total_moves: usize,
+    pub min_moves: Option<usize>,
+    pub max_moves: Option<usize>,
+}
+
+impl Tally {
+    fn add(&mut self, moves: usize) {
+        self.games += 1;
+        self.total_moves += moves;
+        self.min_moves = Some(self.min_moves.map_or(moves, |min| min.min(moves)));
+        self.max_moves = Some(self.max_moves.map_or(moves, |max| max.max(moves)));
+    }
+
+    /// Average moves over the games counted, or `None` before any.
+    pub fn mean_moves(&self) -> Option<f64> {
+        if self.games == 0 {
+            None
+        } else {
+            Some(self.total_moves as f64 / self.games as f64)
+        }
+    }
+}
+
+/// Summary statistics over many games.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub wins: Tally,
+    pub losses: Tally,
+    pub ties: Tally,
+    pub cycles: Tally,
+    /// Games stopped by the move limit.
+    pub cut_off: Tally,
+    pub total_wars: usize,
+    /// Cards laid face down in all those wars.
+    pub total_face_down: usize,
+}
+
+impl Stats {
+    /// Count the result of one more game.
+    pub fn add(&mut self, outcome: &Outcome) {
+        let tally = match outcome.score {
+            Score::WinAfter(_) => &mut self.wins,
+            Score::LoseAfter(_) => &mut self.losses,
+            Score::TiedAt(_) => &mut self.ties,
+            Score::Cycle { .. } => &mut self.cycles,
+            Score::FinishWith(_) => &mut self.cut_off,
+        };
+        tally.add(outcome.moves);
+        self.total_wars += outcome.wars;
+        self.total_face_down += outcome.face_down;
+    }
+
+    /// Number of games counted.
+    pub fn games(&self) -> usize {
+        self.tallies().iter().map(|&(_, tally)| tally.games).sum()
+    }
+
+    fn tallies(&self) -> [(&'static str, &Tally); 5] {
+        [
+            ("wins", &self.wins),
+            ("losses", &self.losses),
+            ("ties", &self.ties),
+            ("cycles", &self.cycles),
+            ("cut off", &self.cut_off),
+        ]
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let games = self.games();
+        writeln!(f, "games: {}", games)?;
+        for &(name, tally) in &self.tallies() {
+            write!(f, "{}: {}", name, tally.games)?;
+            if let (Some(mean), Some(min), Some(max)) =
+                (tally.mean_moves(), tally.min_moves, tally.max_moves)
+            {
+                write!(
+                    f,
+                    " ({:.1}%), moves mean {:.1}, min {}, max {}",
+                    100.0 * tally.games as f64 / games as f64,
+                    mean,
+                    min,
+                    max
+                )?;
+            }
+            writeln!(f)?;
+        }
+        if games > 0 {
+            writeln!(f, "wars per game: {:.1}", self.total_wars as f64 / games as f64)?;
+            write!(
+                f,
+                "cards face down per game: {:.1}",
+                self.total_face_down as f64 / games as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn outcome(score: Score, moves: usize, wars: usize) -> Outcome {
+        Outcome {
+            score,
+            moves,
+            wars,
+            face_down: 3 * wars,
+            computer_cards: 0,
+            player_cards: 0,
+            standings: vec![vec![1], vec![0]],
+        }
+    }
+
+    #[test]
+    fn totals() {
+        let mut stats = Stats::default();
+        stats.add(&outcome(Score::WinAfter(10), 10, 1));
+        stats.add(&outcome(Score::WinAfter(30), 30, 2));
+        stats.add(&outcome(Score::LoseAfter(5), 5, 0));
+        stats.add(&outcome(Score::FinishWith(40), 100, 9));
+
+        assert_eq!(stats.games(), 4);
+        assert_eq!(stats.wins.mean_moves(), Some(20.0));
+        assert_eq!((stats.wins.min_moves, stats.wins.max_moves), (Some(10), Some(30)));
+        assert_eq!(stats.ties.mean_moves(), None);
+        assert_eq!(stats.total_wars, 12);
+        assert_eq!(stats.total_face_down, 36);
+        assert_eq!(
+            stats.to_string(),
+            "games: 4\n\
+             wins: 2 (50.0%), moves mean 20.0, min 10, max 30\n\
+             losses: 1 (25.0%), moves mean 5.0, min 5, max 5\n\
+             ties: 0\n\
+             cycles: 0\n\
+             cut off: 1 (25.0%), moves mean 100.0, min 100, max 100\n\
+             wars per game: 3.0\n\
+             cards face down per game: 9.0"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt;
+use std::str::FromStr;
+
+use std::cmp::Ordering;
+
+use {Card, Deck, Rules, Shuffle, Xoshiro256StarStar};
+
+/// What a player can see when arranging the cards they just won: the cards
+/// played to the trick and how many cards everyone holds, but not the order
+/// of anyone's deck, and the rules of the game.
+#[derive(Debug, Clone, Copy)]
+pub struct View<'a> {
+    seat: usize,
+    moves: usize,
+    piles: &'a [Deck],
+    decks: &'a [Deck],
+    rules: &'a Rules,
+}
+
+impl<'a> View<'a> {
+    /// The view of `seat` after winning trick number `moves`, counting from
+    /// 0, with `piles` on the table and `decks` before collecting them,
+    /// in a game played under `rules`.
+    pub fn new(seat: usize, moves: usize, piles: &'a [Deck], decks: &'a [Deck], rules: &'a Rules) -> Self {
+        View {
+            seat,
+            moves,
+            piles,
+            decks,
+            rules,
+        }
+    }
+
+    /// The seat that won the trick.
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    /// Tricks played before this one. Strategies that look at it are not
+    /// deterministic, see `Strategy::is_deterministic`.
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    /// The cards played to the trick by each seat.
+    pub fn piles(&self) -> &'a [Deck] {
+        self.piles
+    }
+
+    /// Number of seats at the table.
+    pub fn players(&self) -> usize {
+        self.decks.len()
+    }
+
+    /// Cards `seat` holds, not counting those on the table.
+    pub fn cards_held(&self, seat: usize) -> usize {
+        self.decks[seat].len()
+    }
+
+    /// The rules of the game.
+    pub fn rules(&self) -> &'a Rules {
+        self.rules
+    }
+
+    /// Whether no rank in play beats `card` under the rules.
+    pub fn is_unbeaten(&self, card: Card) -> bool {
+        let beaten = |&rank| self.rules.compare(Card::new(rank, card.suit()), card) == Ordering::Greater;
+        !self.rules.ranks.iter().any(beaten)
+    }
+}
+
+/// Decides the order in which a player puts the cards they win on the
+/// bottom of their deck.
+///
+/// The engine consults the strategy every time a trick is won, after the
+/// winner's `PickupPolicy` has put the cards in order. Strategies may only
+/// reorder the cards they are given.
+///
+/// Boxes of strategies are strategies, and a slice of strategies gives one
+/// to each seat, computer first; seats past its end keep the order they
+/// picked up. `NoStrategy` keeps every order, at no cost.
+pub trait Strategy {
+    /// Reorder `cards`, just won by `view.seat()`, top first.
+    fn arrange(&mut self, cards: &mut [Card], view: &View);
+
+    /// Whether `seat` arranges its cards at all. The engine skips the
+    /// strategy for seats that do not.
+    fn arranges(&self, _seat: usize) -> bool {
+        true
+    }
+
+    /// Whether the order depends only on the cards, the number of cards
+    /// each seat holds and the rules. A deterministic strategy must not look
+    /// at `View::moves`, since a position seen again later in the game has
+    /// to be arranged the same way. Games where the order depends on
+    /// anything else cannot be checked for loops.
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+}
+
+/// A strategy that keeps every order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoStrategy;
+
+impl Strategy for NoStrategy {
+    #[inline(always)]
+    fn arrange(&mut self, _cards: &mut [Card], _view: &View) {}
+
+    #[inline(always)]
+    fn arranges(&self, _seat: usize) -> bool {
+        false
+    }
+}
+
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    fn arrange(&mut self, cards: &mut [Card], view: &View) {
+        (**self).arrange(cards, view)
+    }
+
+    fn arranges(&self, seat: usize) -> bool {
+        (**self).arranges(seat)
+    }
+
+    fn is_deterministic(&self) -> bool {
+        (**self).is_deterministic()
+    }
+}
+
+impl<S: Strategy> Strategy for [S] {
+    fn arrange(&mut self, cards: &mut [Card], view: &View) {
+        if let Some(strategy) = self.get_mut(view.seat()) {
+            strategy.arrange(cards, view);
+        }
+    }
+
+    fn arranges(&self, seat: usize) -> bool {
+        self.get(seat).is_some_and(|strategy| strategy.arranges(seat))
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.iter().all(|strategy| strategy.is_deterministic())
+    }
+}
+
+/// Put the strongest cards first, by `Rules::strength`, so they come back
+/// into play soonest. Cards of the same strength keep their order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Greedy;
+
+impl Strategy for Greedy {
+    fn arrange(&mut self, cards: &mut [Card], view: &View) {
+        cards.sort_by_key(|&card| ::std::cmp::Reverse(view.rules().strength(card)));
+    }
+}
+
+/// Put the cards no rank beats last, saving them to win the wars of later
+/// tricks: the aces under the standard comparison, the twos in Peace. Under
+/// `Comparison::Cyclic` every rank can be beaten, so the order is kept. The
+/// other cards keep their order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeepAcesBack;
+
+impl Strategy for KeepAcesBack {
+    fn arrange(&mut self, cards: &mut [Card], view: &View) {
+        cards.sort_by_key(|&card| view.is_unbeaten(card));
+    }
+}
+
+/// Shuffle the cards with a seeded generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomOrder {
+    rng: Xoshiro256StarStar,
+}
+
+impl RandomOrder {
+    /// A strategy shuffling with a generator seeded with the given words.
+    pub fn new(seed: &[usize]) -> Self {
+        RandomOrder {
+            rng: Xoshiro256StarStar::from_seed(seed),
+        }
+    }
+}
+
+impl Strategy for RandomOrder {
+    fn arrange(&mut self, cards: &mut [Card], _view: &View) {
+        let mut deck = Deck::from_vec(cards.to_vec());
+        deck.shuffle(Shuffle::V1, &mut self.rng);
+        for (card, shuffled) in cards.iter_mut().zip(deck.iter()) {
+            *card = *shuffled;
+        }
+    }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
+
+/// The built-in strategies, by name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StrategyKind {
+    /// `NoStrategy`.
+    #[default]
+    None,
+    Greedy,
+    KeepAcesBack,
+    /// `RandomOrder` seeded with the given word.
+    Random(usize),
+}
+
+impl StrategyKind {
+    /// A fresh instance of the strategy.
+    pub fn strategy(self) -> Box<dyn Strategy + Send> {
+        match self {
+            StrategyKind::None => Box::new(NoStrategy),
+            StrategyKind::Greedy => Box::new(Greedy),
+            StrategyKind::KeepAcesBack => Box::new(KeepAcesBack),
+            StrategyKind::Random(seed) => Box::new(RandomOrder::new(&[seed])),
+        }
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = String;
+
+    /// Parse `none`, `greedy`, `keep-aces-back` or `random:SEED`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "none" => Ok(StrategyKind::None),
+            "greedy" => Ok(StrategyKind::Greedy),
+            "keep-aces-back" => Ok(StrategyKind::KeepAcesBack),
+            _ => match s.strip_prefix("random:").map(str::parse) {
+                Some(Ok(seed)) => Ok(StrategyKind::Random(seed)),
+                _ => Err(format!(
+                    "unknown strategy {:?}, expected none, greedy, keep-aces-back or random:SEED",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StrategyKind::None => f.write_str("none"),
+            StrategyKind::Greedy => f.write_str("greedy"),
+            StrategyKind::KeepAcesBack => f.write_str("keep-aces-back"),
+            StrategyKind::Random(seed) => write!(f, "random:{}", seed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Comparison;
+
+    fn arrange<S: Strategy + ?Sized>(strategy: &mut S, seat: usize, cards: &str) -> Deck {
+        arrange_under(strategy, &Rules::default(), seat, cards)
+    }
+
+    fn arrange_under<S: Strategy + ?Sized>(strategy: &mut S, rules: &Rules, seat: usize, cards: &str) -> Deck {
+        let decks = vec![Deck::new_empty(); 2];
+        let mut cards = cards.parse::<Deck>().unwrap().to_vec();
+        strategy.arrange(&mut cards, &View::new(seat, 0, &decks, &decks, rules));
+        Deck::from_vec(cards)
+    }
+
+    fn deck(cards: &str) -> Deck {
+        cards.parse().unwrap()
+    }
+
+    #[test]
+    fn built_ins() {
+        assert_eq!(arrange(&mut NoStrategy, 0, "4 A 2 K A"), deck("4 A 2 K A"));
+        assert_eq!(arrange(&mut Greedy, 0, "4 AS 2 K AD"), deck("AS AD K 4 2"));
+        assert_eq!(arrange(&mut KeepAcesBack, 0, "4 AS 2 K AD"), deck("4 2 K AS AD"));
+
+        let shuffled = arrange(&mut RandomOrder::new(&[2]), 0, "2 3 4 5 6 7 8 9");
+        assert_eq!(arrange(&mut RandomOrder::new(&[2]), 0, "2 3 4 5 6 7 8 9"), shuffled);
+        let mut cards = shuffled.to_vec();
+        cards.sort_by_key(|card| card.to_byte());
+        assert_eq!(Deck::from_vec(cards), deck("2 3 4 5 6 7 8 9"));
+    }
+
+    #[test]
+    fn follow_the_comparison() {
+        let peace = Rules {
+            comparison: Comparison::Peace,
+            ..Rules::default()
+        };
+        assert_eq!(arrange_under(&mut Greedy, &peace, 0, "4 AS 2 K AD"), deck("2 4 K AS AD"));
+        assert_eq!(arrange_under(&mut KeepAcesBack, &peace, 0, "4 AS 2 K AD"), deck("4 AS K AD 2"));
+
+        let cyclic = Rules {
+            comparison: Comparison::Cyclic,
+            ..Rules::default()
+        };
+        assert_eq!(arrange_under(&mut Greedy, &cyclic, 0, "4 AS 2 K AD"), deck("AS K AD 4 2"));
+        assert_eq!(arrange_under(&mut KeepAcesBack, &cyclic, 0, "4 AS 2 K AD"), deck("4 AS 2 K AD"));
+
+        let jokers = Rules {
+            jokers: 1,
+            ..Rules::default()
+        };
+        assert_eq!(arrange_under(&mut Greedy, &jokers, 0, "4 AS JK"), deck("JK AS 4"));
+        assert_eq!(arrange_under(&mut KeepAcesBack, &jokers, 0, "JK 4 AS"), deck("4 JK AS"));
+    }
+
+    #[test]
+    fn one_per_seat() {
+        let mut seats: Vec<Box<dyn Strategy + Send>> = vec![StrategyKind::Greedy.strategy()];
+        assert!(seats[..].arranges(0));
+        assert!(!seats[..].arranges(1));
+        assert_eq!(arrange(&mut seats[..], 0, "2 A"), deck("A 2"));
+        assert_eq!(arrange(&mut seats[..], 1, "2 A"), deck("2 A"));
+
+        seats.push(StrategyKind::Random(1).strategy());
+        assert!(!seats[..].is_deterministic());
+    }
+
+    #[test]
+    fn notation() {
+        for &kind in &[
+            StrategyKind::None,
+            StrategyKind::Greedy,
+            StrategyKind::KeepAcesBack,
+            StrategyKind::Random(9),
+        ] {
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+        assert!("random".parse::<StrategyKind>().is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use rayon::prelude::*;
+use std::ops::Range;
+
+use {play_game_outcome, GameState, Generator, Outcome, Rules};
+
+/// Number of games handed to the thread pool at a time. Results are
+/// reported chunk by chunk, so this bounds how many are held in memory.
+const CHUNK_SIZE: usize = 1 << 14;
+
+/// A contiguous range of seeds. Each seed is the `prefix` words followed by
+/// a single counter word taken from `counters`, expanded by `generator`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedRange {
+    pub prefix: Vec<usize>,
+    pub counters: Range<usize>,
+    pub generator: Generator,
+}
+
+impl SeedRange {
+    /// Single-word seeds `start..end`.
+    pub fn new(start: usize, end: usize) -> Self {
+        SeedRange {
+            prefix: Vec::new(),
+            counters: start..end,
+            generator: Generator::default(),
+        }
+    }
+
+    /// The full seed for the given counter.
+    pub fn seed(&self, counter: usize) -> Vec<usize> {
+        let mut seed = self.prefix.clone();
+        seed.push(counter);
+        seed
+    }
+}
+
+/// Play the game dealt by the given seed.
+pub fn play_seed(seed: &[usize], generator: Generator, rules: &Rules) -> Outcome {
+    let mut rng = generator.seed(seed);
+    play_game_outcome(GameState::new(rules, &mut rng), rules)
+}
+
+/// Play every seed in the range on the current rayon thread pool.
+///
+/// Games are spread across threads, but `each` is called on the calling
+/// thread with the counter and outcome of every seed in increasing counter
+/// order, regardless of scheduling.
+pub fn sweep<F>(seeds: &SeedRange, rules: &Rules, mut each: F)
+where
+    F: FnMut(usize, Outcome),
+{
+    let mut start = seeds.counters.start;
+    while start < seeds.counters.end {
+        let end = seeds.counters.end.min(start.saturating_add(CHUNK_SIZE));
+        let outcomes: Vec<Outcome> = (start..end)
+            .into_par_iter()
+            .map(|counter| play_seed(&seeds.seed(counter), seeds.generator, rules))
+            .collect();
+        for (counter, outcome) in (start..end).zip(outcomes) {
+            each(counter, outcome);
+        }
+        start = end;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sweep_in_seed_order() {
+        let rules = Rules {
+            copies_per_rank: 1,
+            ..Rules::default()
+        };
+        let seeds = SeedRange {
+            prefix: vec![7, 8],
+            counters: 3..40,
+            generator: Generator::Xoshiro256,
+        };
+
+        let mut seen = Vec::new();
+        sweep(&seeds, &rules, |counter, outcome| seen.push((counter, outcome)));
+
+        let expected: Vec<_> = (3..40)
+            .map(|counter| (counter, play_seed(&[7, 8, counter], Generator::Xoshiro256, &rules)))
+            .collect();
+        assert_eq!(seen, expected);
+    }
+}
@@ -0,0 +1,179 @@
+use rayon::prelude::*;
+use std::fmt;
+
+use {play_game_with_strategy, Deck, GameState, Generator, NoObserver, Rules, Score, StrategyKind};
+
+/// Settings for `tournament`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tournament {
+    /// Deals played by every pair of strategies. Each deal is played twice,
+    /// with the strategies swapping decks.
+    pub deals: usize,
+    /// Seed word placed before the deal number to deal each game.
+    pub seed: usize,
+    pub generator: Generator,
+}
+
+/// The games one strategy played against another, from its point of view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: usize,
+    pub losses: usize,
+    /// Ties, cycles and games cut off by the move limit.
+    pub draws: usize,
+}
+
+impl Record {
+    /// Number of games played.
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    fn add(&mut self, other: &Record) {
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.draws += other.draws;
+    }
+
+    /// The same games from the opponent's point of view.
+    fn reversed(&self) -> Record {
+        Record {
+            wins: self.losses,
+            losses: self.wins,
+            draws: self.draws,
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.wins, self.losses, self.draws)
+    }
+}
+
+/// The results of a tournament: `records[i][j]` holds the games entrant `i`
+/// played against entrant `j`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Results {
+    pub entrants: Vec<StrategyKind>,
+    pub records: Vec<Vec<Record>>,
+}
+
+impl Results {
+    /// Every game entrant `i` played.
+    pub fn total(&self, i: usize) -> Record {
+        let mut total = Record::default();
+        for record in &self.records[i] {
+            total.add(record);
+        }
+        total
+    }
+
+    /// The entrants from most wins to fewest, ties in entry order.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.entrants.len()).collect();
+        ranking.sort_by_key(|&i| ::std::cmp::Reverse(self.total(i).wins));
+        ranking
+    }
+}
+
+impl fmt::Display for Results {
+    /// One line per entrant, best first, with its total record and its
+    /// record against each other entrant as wins-losses-draws.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (place, i) in self.ranking().into_iter().enumerate() {
+            write!(f, "{}. {} {}", place + 1, self.entrants[i], self.total(i))?;
+            for (j, record) in self.records[i].iter().enumerate() {
+                if j != i {
+                    write!(f, ", vs {} {}", self.entrants[j], record)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Play every pair of `entrants` against each other under `rules`, which
+/// must be for two players.
+///
+/// Both decks of every deal are shuffled from the seed `[config.seed,
+/// deal]`, and each deal is played once with each strategy on each deck,
+/// so neither side has the better cards. A strategy wins a game when the
+/// other side runs out of cards. Deals are played on the current rayon
+/// thread pool, and the results do not depend on scheduling.
+pub fn tournament(entrants: &[StrategyKind], rules: &Rules, config: &Tournament) -> Results {
+    assert_eq!(rules.players, 2, "tournaments are between two players");
+    let n = entrants.len();
+    let mut records = vec![vec![Record::default(); n]; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            let record = (0..config.deals)
+                .into_par_iter()
+                .map(|deal| {
+                    let mut rng = config.generator.seed(&[config.seed, deal]);
+                    let decks = vec![Deck::new_shuffle(rules, &mut *rng), Deck::new_shuffle(rules, &mut *rng)];
+                    let mut record = play(&decks, [entrants[i], entrants[j]], rules);
+                    record.add(&play(&decks, [entrants[j], entrants[i]], rules).reversed());
+                    record
+                })
+                .reduce(Record::default, |mut a, b| {
+                    a.add(&b);
+                    a
+                });
+            records[i][j] = record;
+            records[j][i] = record.reversed();
+        }
+    }
+    Results {
+        entrants: entrants.to_vec(),
+        records,
+    }
+}
+
+/// Play one game between `decks`, the computer seat using the first
+/// strategy, and return the result from its point of view.
+fn play(decks: &[Deck], seats: [StrategyKind; 2], rules: &Rules) -> Record {
+    let mut strategies = [seats[0].strategy(), seats[1].strategy()];
+    let game = GameState::with_players(decks.to_vec(), 0);
+    let outcome = play_game_with_strategy(game, rules, &mut strategies[..], &mut NoObserver);
+    let mut record = Record::default();
+    match outcome.score {
+        Score::WinAfter(_) => record.losses += 1,
+        Score::LoseAfter(_) => record.wins += 1,
+        Score::TiedAt(_) | Score::Cycle { .. } | Score::FinishWith(_) => record.draws += 1,
+    }
+    record
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_robin() {
+        let rules = Rules {
+            copies_per_rank: 1,
+            ..Rules::default()
+        };
+        let config = Tournament {
+            deals: 20,
+            seed: 3,
+            generator: Generator::Xoshiro256,
+        };
+        let entrants = [StrategyKind::None, StrategyKind::Greedy, StrategyKind::Random(1)];
+        let results = tournament(&entrants, &rules, &config);
+
+        assert_eq!(results, tournament(&entrants, &rules, &config));
+        for i in 0..3 {
+            assert_eq!(results.records[i][i], Record::default());
+            assert_eq!(results.total(i).games(), 2 * 2 * 20);
+            for j in 0..3 {
+                assert_eq!(results.records[i][j], results.records[j][i].reversed());
+            }
+        }
+        let mut ranking = results.ranking();
+        ranking.sort();
+        assert_eq!(ranking, vec![0, 1, 2]);
+    }
+}